@@ -2,53 +2,104 @@ use std::sync::Arc;
 
 mod bucket;
 mod builder;
+mod clock;
 mod settings;
 pub use bucket::BucketClient;
 pub use builder::{BuilderError, S3ClientBuilder};
+pub use clock::*;
+use futures::{Stream, TryStreamExt, stream};
 use http::HeaderValue;
 use reqwest::Response;
 use tracing::{debug, field::Empty};
 use tux_io_s3_types::{
-    list::buckets::ListAllMyBuckets,
+    S3ContentError,
+    list::buckets::{Bucket, ListAllMyBuckets},
     region::{RegionType, S3Region},
 };
 pub mod http_client;
 use url::Url;
 pub mod inner;
 use crate::{
-    S3Error,
-    client::inner::S3ClientInner,
-    command::{AccountCommandType, CommandType, list::buckets::ListBuckets},
+    S3Error, S3Result,
+    client::{
+        http_client::HttpClient,
+        inner::{RequestTarget, S3ClientInner},
+    },
+    command::{
+        AccountCommandType, CommandType,
+        list::buckets::{ListBuckets, ListBucketsRequest},
+    },
 };
+mod endpoint;
 mod errors;
+mod interceptor;
+mod limiter;
+mod metrics;
+mod presigned_upload;
+mod retry;
+pub use endpoint::*;
 pub use errors::*;
+pub use interceptor::*;
+pub use limiter::ConcurrencyLimit;
+pub use metrics::*;
+pub use presigned_upload::*;
+pub use retry::*;
 pub use settings::*;
-pub struct S3Client {
-    pub(crate) client: Arc<S3ClientInner>,
+pub struct S3Client<Client: HttpClient = reqwest::Client> {
+    pub(crate) client: Arc<S3ClientInner<Client>>,
 }
-impl S3Client {
+impl<Client: HttpClient> S3Client<Client> {
     pub fn get_region(&self) -> &S3Region {
         &self.client.region
     }
+    /// Returns the first page of buckets in the region of the client.
+    ///
     /// Requires
     ///   - `s3:ListAllMyBuckets` permission
     pub async fn list_my_buckets(&self) -> Result<ListAllMyBuckets, S3Error> {
-        let command = ListBuckets {
-            region: &self.client.region,
-            continuation_token: None,
-            max_buckets: None,
-            prefix: None,
-        };
-        let response = self.execute_command(command).await?;
+        let command = ListBuckets::default().with_bucket_region(self.client.region.name());
+        self.list_buckets(command).await
+    }
+    /// Returns a single page of buckets. Use [ListAllMyBuckets::continuation_token] to request the next page.
+    ///
+    /// Requires
+    ///   - `s3:ListAllMyBuckets` permission
+    pub async fn list_buckets(
+        &self,
+        request: impl Into<ListBucketsRequest<'_>>,
+    ) -> Result<ListAllMyBuckets, S3Error> {
+        let response = self.execute_command(request.into()).await?;
         if !response.status().is_success() {
-            return Err(HttpResponseError::from(response).into());
+            return Err(S3Error::from_response(response).await);
         }
+        let body = response.text().await?;
         let list_buckets: ListAllMyBuckets =
-            quick_xml::de::from_str(&response.text().await?).unwrap();
+            quick_xml::de::from_str(&body).map_err(S3ContentError::from)?;
         Ok(list_buckets)
     }
+    /// Returns every bucket matching the request. Pages are requested as the stream is read.
+    ///
+    /// Requires
+    ///   - `s3:ListAllMyBuckets` permission
+    pub fn list_buckets_stream<'request>(
+        &'request self,
+        request: impl Into<ListBucketsRequest<'request>>,
+    ) -> impl Stream<Item = S3Result<Bucket>> + 'request {
+        stream::try_unfold(Some(request.into()), move |request| async move {
+            let Some(request) = request else {
+                return Ok(None);
+            };
+            let page = self.list_buckets(request.clone()).await?;
+            let next = page
+                .continuation_token
+                .map(|token| request.with_continuation_token(token));
+            let buckets = stream::iter(page.buckets.buckets.into_iter().map(Ok));
+            Ok::<_, S3Error>(Some((buckets, next)))
+        })
+        .try_flatten()
+    }
 
-    pub fn open_bucket(&self, bucket: &str) -> BucketClient {
+    pub fn open_bucket(&self, bucket: &str) -> BucketClient<Client> {
         BucketClient {
             client: Arc::clone(&self.client),
             bucket: bucket.to_string(),
@@ -69,11 +120,18 @@ impl S3Client {
             status_code = Empty
         );
         let _enter = span.enter();
-        let url = self.url()?;
-        debug!(%url, "Executing S3 command");
-        let host = HeaderValue::from_str(&self.host()?)?;
+        let endpoint =
+            self.client
+                .resolve_endpoint(&self.client.region, None, Some(command.name()))?;
+        debug!(url = %endpoint.url, "Executing S3 command");
+        let target = RequestTarget {
+            url: endpoint.url,
+            host: HeaderValue::from_str(&endpoint.host)?,
+            region: endpoint.signing_region,
+            bucket: None,
+        };
 
-        let response = self.client.execute_command(command, url, host).await?;
+        let response = self.client.execute_command(command, target).await?;
 
         span.record("status_code", response.status().as_u16());
         debug!("S3 Command Responded");
@@ -82,32 +140,15 @@ impl S3Client {
     }
 
     pub fn url(&self) -> Result<Url, S3Error> {
-        match self.client.access_type {
-            AccessType::PathStyle => {
-                let url = self.client.region.endpoint_url();
-
-                Ok(url)
-            }
-            AccessType::VirtualHostedStyle => {
-                let raw_host = self.client.region.endpoint();
-                let url = format!("{}://{}", self.client.region.schema(), raw_host);
-                Url::parse(&url).map_err(S3Error::from)
-            }
-        }
+        let endpoint = self
+            .client
+            .resolve_endpoint(&self.client.region, None, None)?;
+        Ok(endpoint.url)
     }
     pub fn host(&self) -> Result<String, S3Error> {
-        match self.client.access_type {
-            AccessType::PathStyle => {
-                if let Some(port) = self.client.region.endpoint_url().port() {
-                    Ok(format!("{}:{}", self.client.region.endpoint(), port))
-                } else {
-                    Ok(self.client.region.endpoint().to_string())
-                }
-            }
-            AccessType::VirtualHostedStyle => {
-                let raw_host = self.client.region.endpoint();
-                Ok(raw_host.to_string())
-            }
-        }
+        let endpoint = self
+            .client
+            .resolve_endpoint(&self.client.region, None, None)?;
+        Ok(endpoint.host)
     }
 }
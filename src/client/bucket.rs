@@ -1,65 +1,60 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
+use futures::{Stream, TryStreamExt, future::Either, stream};
 use http::{HeaderValue, StatusCode};
 use reqwest::Response;
 use tracing::{debug, field::Empty};
-use tux_io_s3_types::{S3ContentError, list::v2, region::RegionType, tag::OwnedTaggingSet};
+use tux_io_s3_types::{
+    S3ContentError,
+    delete::{Delete, DeleteResult},
+    list::{ListEntry, v1, v2},
+    region::S3Region,
+    tag::OwnedTaggingSet,
+};
 use url::Url;
 
 use crate::{
-    S3Error,
-    client::{S3ClientInner, errors::HttpResponseError, settings::AccessType},
+    S3Error, S3Result,
+    client::{
+        S3ClientInner, http_client::HttpClient, inner::RequestTarget, settings::ListObjectsVersion,
+    },
     command::{
         BucketCommandType, CommandType,
+        delete::DeleteObjects,
         get::{GetObject, GetObjectResponse, GetObjectTagging},
         head::{HeadObject, HeadObjectResponse},
-        list::ListObjectsV2,
+        list::{ListObjectsV1, ListObjectsV2},
     },
+    credentials::post_policy::{PostPolicyBuilder, PresignedPost},
 };
 #[derive(Debug, Clone)]
-pub struct BucketClient {
-    pub(crate) client: Arc<S3ClientInner>,
+pub struct BucketClient<Client: HttpClient = reqwest::Client> {
+    pub(crate) client: Arc<S3ClientInner<Client>>,
     pub(crate) bucket: String,
 }
-impl BucketClient {
+impl<Client: HttpClient> BucketClient<Client> {
+    /// The region requests for this bucket are signed for.
+    ///
+    /// Starts as the region of the client and changes if S3 redirects to the region of the bucket.
+    pub fn region(&self) -> S3Region {
+        self.client.bucket_region(&self.bucket)
+    }
     pub fn url(&self) -> Result<Url, S3Error> {
-        match self.client.access_type {
-            AccessType::PathStyle => {
-                let mut url = self.client.region.endpoint_url();
-                url.set_path(&format!("/{}/", self.bucket));
-                Ok(url)
-            }
-            AccessType::VirtualHostedStyle => {
-                let raw_host = self.client.region.endpoint();
-                let url = format!(
-                    "{}://{}.{}",
-                    self.client.region.schema(),
-                    self.bucket,
-                    raw_host
-                );
-                Url::parse(&url).map_err(S3Error::from)
-            }
-        }
+        let endpoint = self
+            .client
+            .resolve_endpoint(&self.region(), Some(&self.bucket), None)?;
+        Ok(endpoint.url)
     }
     pub fn host(&self) -> Result<String, S3Error> {
-        match self.client.access_type {
-            AccessType::PathStyle => {
-                if let Some(port) = self.client.region.endpoint_url().port() {
-                    Ok(format!("{}:{}", self.client.region.endpoint(), port))
-                } else {
-                    Ok(self.client.region.endpoint().to_string())
-                }
-            }
-            AccessType::VirtualHostedStyle => {
-                let raw_host = self.client.region.endpoint();
-                Ok(format!("{}.{}", self.bucket, raw_host))
-            }
-        }
+        let endpoint = self
+            .client
+            .resolve_endpoint(&self.region(), Some(&self.bucket), None)?;
+        Ok(endpoint.host)
     }
     /// Calls the HEAD operation on the object at the given path.
     ///
     /// For the sake of consistency, the return is an `Option<Response>`. Ok(None) means the object does not exist.
-    /// Any other HTTP Error will be returned as [S3Error::HttpError].
+    /// Any other HTTP Error will be returned as [S3Error::Service].
     pub async fn head_object(&self, path: &str) -> Result<Option<HeadObjectResponse>, S3Error> {
         let command = HeadObject { key: path };
         let result = self.execute_command(command).await?;
@@ -67,14 +62,14 @@ impl BucketClient {
             return Ok(None);
         }
         if !result.status().is_success() {
-            return Err(HttpResponseError::from(result).into());
+            return Err(S3Error::from_response(result).await);
         }
         Ok(Some(HeadObjectResponse(result)))
     }
     /// Returns the tagging set for the object at the given path.
     ///
     /// If the object does not exist, returns `None`.
-    /// Any other HTTP Error will be returned as [S3Error::HttpError].
+    /// Any other error response will be returned as [S3Error::Service].
     pub async fn get_object_tagging(&self, key: &str) -> Result<Option<OwnedTaggingSet>, S3Error> {
         let command = GetObjectTagging {
             key,
@@ -85,7 +80,7 @@ impl BucketClient {
             return Ok(None);
         }
         if !result.status().is_success() {
-            return Err(HttpResponseError::from(result).into());
+            return Err(S3Error::from_response(result).await);
         }
         let body = result.text().await?;
 
@@ -93,6 +88,9 @@ impl BucketClient {
             quick_xml::de::from_str(&body).map_err(S3ContentError::from)?;
         Ok(Some(tagging_set))
     }
+    /// Lists objects with the V2 API.
+    ///
+    /// Keys are decoded if the request set an [tux_io_s3_types::list::EncodingType].
     pub async fn list_objects_v2(
         &self,
         list_objects: impl Into<ListObjectsV2<'_>>,
@@ -100,14 +98,176 @@ impl BucketClient {
         let command = list_objects.into();
         let response = self.execute_command(command).await?;
         if !response.status().is_success() {
-            return Err(HttpResponseError::from(response).into());
+            return Err(S3Error::from_response(response).await);
+        }
+        let body = response.text().await?;
+        debug!("ListObjects response body: {}", body);
+        let mut data: v2::ListBucketResult =
+            quick_xml::de::from_str(&body).map_err(S3ContentError::from)?;
+        data.decode_url_encoding();
+        Ok(data)
+    }
+    /// Requests pages until the listing is no longer truncated.
+    ///
+    /// `max_total_keys` caps the number of objects and common prefixes over all pages.
+    pub fn list_objects_v2_pages<'request>(
+        &'request self,
+        list_objects: impl Into<ListObjectsV2<'request>>,
+        max_total_keys: Option<usize>,
+    ) -> impl Stream<Item = S3Result<v2::ListBucketResult>> + 'request {
+        let state = (Some(list_objects.into()), max_total_keys);
+        stream::try_unfold(state, move |(command, remaining)| async move {
+            let Some(mut command) = command else {
+                return Ok(None);
+            };
+            if let Some(remaining) = remaining {
+                if remaining == 0 {
+                    return Ok(None);
+                }
+                command.max_keys =
+                    Some(command.max_keys.map_or(remaining, |max| max.min(remaining)));
+            }
+            let page = self.list_objects_v2(command.clone()).await?;
+            let remaining = remaining.map(|remaining| remaining.saturating_sub(page.entry_count()));
+            let next = match &page.next_continuation_token {
+                Some(token) if page.is_truncated => {
+                    Some(command.with_continuation_token(token.clone()))
+                }
+                _ => None,
+            };
+            Ok::<_, S3Error>(Some((page, (next, remaining))))
+        })
+    }
+    /// Yields the objects and common prefixes of every page of [BucketClient::list_objects_v2_pages]
+    pub fn list_objects_v2_stream<'request>(
+        &'request self,
+        list_objects: impl Into<ListObjectsV2<'request>>,
+        max_total_keys: Option<usize>,
+    ) -> impl Stream<Item = S3Result<ListEntry>> + 'request {
+        self.list_objects_v2_pages(list_objects, max_total_keys)
+            .map_ok(|page| stream::iter(page.into_entries().into_iter().map(Ok)))
+            .try_flatten()
+    }
+    /// Lists objects with the V1 API. Only use it for endpoints that do not support [BucketClient::list_objects_v2]
+    ///
+    /// Keys are decoded if the request set an [tux_io_s3_types::list::EncodingType].
+    pub async fn list_objects_v1(
+        &self,
+        list_objects: impl Into<ListObjectsV1<'_>>,
+    ) -> Result<v1::ListBucketResult, S3Error> {
+        let command = list_objects.into();
+        let response = self.execute_command(command).await?;
+        if !response.status().is_success() {
+            return Err(S3Error::from_response(response).await);
         }
         let body = response.text().await?;
         debug!("ListObjects response body: {}", body);
-        let data: v2::ListBucketResult =
+        let mut data: v1::ListBucketResult =
             quick_xml::de::from_str(&body).map_err(S3ContentError::from)?;
+        data.decode_url_encoding();
         Ok(data)
     }
+    /// Requests V1 pages until the listing is no longer truncated.
+    ///
+    /// `max_total_keys` caps the number of objects and common prefixes over all pages.
+    pub fn list_objects_v1_pages<'request>(
+        &'request self,
+        list_objects: impl Into<ListObjectsV1<'request>>,
+        max_total_keys: Option<usize>,
+    ) -> impl Stream<Item = S3Result<v1::ListBucketResult>> + 'request {
+        let state = (Some(list_objects.into()), max_total_keys);
+        stream::try_unfold(state, move |(command, remaining)| async move {
+            let Some(mut command) = command else {
+                return Ok(None);
+            };
+            if let Some(remaining) = remaining {
+                if remaining == 0 {
+                    return Ok(None);
+                }
+                command.max_keys =
+                    Some(command.max_keys.map_or(remaining, |max| max.min(remaining)));
+            }
+            let page = self.list_objects_v1(command.clone()).await?;
+            let remaining = remaining.map(|remaining| remaining.saturating_sub(page.entry_count()));
+            let next = page
+                .next_page_marker()
+                .map(|marker| command.with_marker(marker.to_owned()));
+            Ok::<_, S3Error>(Some((page, (next, remaining))))
+        })
+    }
+    /// Yields the objects and common prefixes of every page of [BucketClient::list_objects_v1_pages]
+    pub fn list_objects_v1_stream<'request>(
+        &'request self,
+        list_objects: impl Into<ListObjectsV1<'request>>,
+        max_total_keys: Option<usize>,
+    ) -> impl Stream<Item = S3Result<ListEntry>> + 'request {
+        self.list_objects_v1_pages(list_objects, max_total_keys)
+            .map_ok(|page| stream::iter(page.into_entries().into_iter().map(Ok)))
+            .try_flatten()
+    }
+    /// Lists objects with the API picked by [crate::client::S3ClientBuilder::with_list_objects_version].
+    ///
+    /// With V1 `start_after` is sent as the marker.
+    /// A continuation token fails with [S3Error::ContinuationTokenWithV1].
+    pub fn list_objects_stream<'request>(
+        &'request self,
+        list_objects: impl Into<ListObjectsV2<'request>>,
+        max_total_keys: Option<usize>,
+    ) -> impl Stream<Item = S3Result<ListEntry>> + 'request {
+        let list_objects = list_objects.into();
+        let has_continuation_token = list_objects.continuation_token.is_some();
+        stream::once(async move {
+            let version = self.list_objects_version().await?;
+            if version == ListObjectsVersion::V1 && has_continuation_token {
+                return Err(S3Error::ContinuationTokenWithV1);
+            }
+            Ok(version)
+        })
+        .map_ok(move |version| match version {
+            ListObjectsVersion::V1 => {
+                Either::Left(self.list_objects_v1_stream(
+                    ListObjectsV1::from(list_objects.clone()),
+                    max_total_keys,
+                ))
+            }
+            _ => Either::Right(self.list_objects_v2_stream(list_objects.clone(), max_total_keys)),
+        })
+        .try_flatten()
+    }
+    /// The ListObjects API used by [BucketClient::list_objects_stream].
+    ///
+    /// With [ListObjectsVersion::Auto] the first call sends a ListObjectsV2 request for one key.
+    /// V1 is used if the endpoint rejects it with `400` or `501` or answers without a `KeyCount`.
+    /// The result is shared by every [BucketClient] of the client.
+    pub async fn list_objects_version(&self) -> Result<ListObjectsVersion, S3Error> {
+        if self.client.list_objects_version != ListObjectsVersion::Auto {
+            return Ok(self.client.list_objects_version);
+        }
+        if let Some(version) = self.client.detected_list_objects_version.get() {
+            return Ok(*version);
+        }
+        let probe = ListObjectsV2::<()>::default().with_max_keys(1);
+        let response = self.execute_command(probe).await?;
+        let version = match response.status() {
+            status if status.is_success() => {
+                let body = response.text().await?;
+                let page: v2::ListBucketResult =
+                    quick_xml::de::from_str(&body).map_err(S3ContentError::from)?;
+                if page.key_count.is_some() {
+                    ListObjectsVersion::V2
+                } else {
+                    ListObjectsVersion::V1
+                }
+            }
+            StatusCode::BAD_REQUEST | StatusCode::NOT_IMPLEMENTED => ListObjectsVersion::V1,
+            _ => return Err(S3Error::from_response(response).await),
+        };
+        debug!(?version, "Detected ListObjects version");
+        Ok(*self
+            .client
+            .detected_list_objects_version
+            .get_or_init(|| version))
+    }
     pub async fn get_object(&self, key: &str) -> Result<Option<GetObjectResponse>, S3Error> {
         let command = GetObject {
             key,
@@ -115,10 +275,66 @@ impl BucketClient {
         };
         let response = self.execute_command(command).await?;
         if !response.status().is_success() {
-            return Err(HttpResponseError::from(response).into());
+            return Err(S3Error::from_response(response).await);
         }
         Ok(Some(GetObjectResponse(response)))
     }
+    /// Deletes the objects with one [DeleteObjects] request
+    pub async fn delete_objects(&self, delete: Delete) -> Result<DeleteResult, S3Error> {
+        let response = self
+            .execute_command(DeleteObjects { content: delete })
+            .await?;
+        if !response.status().is_success() {
+            return Err(S3Error::from_response(response).await);
+        }
+        let result: DeleteResult =
+            quick_xml::de::from_str(&response.text().await?).map_err(S3ContentError::from)?;
+        Ok(result)
+    }
+    /// Creates a presigned url for `command` that can be used without credentials until `expires_in` has passed.
+    ///
+    /// Headers set by the command, such as the content type of a [crate::command::put::PutObject], are signed and must be sent with the request.
+    /// The payload is not signed. `expires_in` must be between 1 second and 7 days.
+    pub async fn presign<T>(&self, command: T, expires_in: Duration) -> Result<Url, S3Error>
+    where
+        T: CommandType + BucketCommandType,
+    {
+        let endpoint = self.client.resolve_endpoint(
+            &self.region(),
+            Some(&self.bucket),
+            Some(command.name()),
+        )?;
+        let target = RequestTarget {
+            url: endpoint.url,
+            host: HeaderValue::from_str(&endpoint.host)?,
+            region: endpoint.signing_region,
+            bucket: Some(&self.bucket),
+        };
+        self.client
+            .presign_command(&command, target, expires_in)
+            .await
+    }
+    /// Starts a [PostPolicyBuilder] for uploads to this bucket. Sign it with [BucketClient::presign_post]
+    pub fn post_policy(&self) -> PostPolicyBuilder<'_> {
+        PostPolicyBuilder::new(self.bucket.as_str())
+    }
+    /// Signs the policy with the credentials of the client and returns the form fields for a POST Object upload.
+    pub async fn presign_post(
+        &self,
+        policy: PostPolicyBuilder<'_>,
+    ) -> Result<PresignedPost, S3Error> {
+        let endpoint =
+            self.client
+                .resolve_endpoint(&self.region(), Some(&self.bucket), Some("PostObject"))?;
+        let credentials = self.client.credentials().await?;
+        let post = policy.sign(
+            endpoint.url,
+            &credentials,
+            &endpoint.signing_region,
+            self.client.signing_time(),
+        )?;
+        Ok(post)
+    }
     /// A low-level method to execute any [BucketCommandType].
     ///
     /// This will return Ok(Response) for any HTTP Response that could be parsed by the HTTP Client.
@@ -142,11 +358,20 @@ impl BucketClient {
         );
         let _enter = span.enter();
         let _enter = span.enter();
-        let url = self.url()?;
-        debug!(%url, "Executing S3 command");
-        let host = HeaderValue::from_str(&self.host()?)?;
+        let endpoint = self.client.resolve_endpoint(
+            &self.region(),
+            Some(&self.bucket),
+            Some(command.name()),
+        )?;
+        debug!(url = %endpoint.url, "Executing S3 command");
+        let target = RequestTarget {
+            url: endpoint.url,
+            host: HeaderValue::from_str(&endpoint.host)?,
+            region: endpoint.signing_region,
+            bucket: Some(&self.bucket),
+        };
 
-        let response = self.client.execute_command(command, url, host).await?;
+        let response = self.client.execute_command(command, target).await?;
 
         span.record("status_code", response.status().as_u16());
         debug!("S3 Command Responded");
@@ -154,3 +379,174 @@ impl BucketClient {
         Ok(response)
     }
 }
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bytes::Bytes;
+    use http::StatusCode;
+    use tux_io_s3_types::{
+        error::S3ErrorCode,
+        region::{OfficialRegion, RegionType},
+        tag::BorrowedTaggingSet,
+    };
+
+    use crate::{
+        S3Error,
+        client::{
+            S3ClientBuilder,
+            http_client::{HttpClient, InMemoryS3},
+        },
+        command::{
+            delete::DeleteObject,
+            get::{GetObject, Ranged},
+            put::{CopyObject, PutTagging, RenameObject},
+        },
+        test::{IN_MEMORY_BUCKET, create_in_memory_bucket_client, init_test_logger},
+    };
+
+    #[tokio::test]
+    async fn object_round_trip() -> anyhow::Result<()> {
+        init_test_logger();
+        let (s3, client) = create_in_memory_bucket_client();
+        s3.put_object(IN_MEMORY_BUCKET, "source.txt", "Hello, World!");
+
+        let response = client
+            .execute_command(GetObject {
+                key: "source.txt",
+                ranged: Some(Ranged {
+                    start: 7,
+                    end: Some(11),
+                }),
+                ..Default::default()
+            })
+            .await?;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.bytes().await?, Bytes::from_static(b"World"));
+
+        let head = client
+            .head_object("source.txt")
+            .await?
+            .expect("Object should exist");
+        assert_eq!(head.0.headers()[http::header::CONTENT_LENGTH], "13");
+
+        let tags = BorrowedTaggingSet::new(vec![("tag", "value").into()]);
+        let response = client
+            .execute_command(PutTagging::new("source.txt", tags))
+            .await?;
+        assert!(response.status().is_success());
+        let tagging = client
+            .get_object_tagging("source.txt")
+            .await?
+            .expect("Object should exist");
+        assert!(tagging.has_tag("tag"));
+
+        let response = client
+            .execute_command(CopyObject::new("test-bucket/source.txt", "copy.txt"))
+            .await?;
+        assert!(response.status().is_success());
+        let response = client
+            .execute_command(RenameObject::new("copy.txt", "renamed.txt"))
+            .await?;
+        assert!(response.status().is_success());
+        assert_eq!(
+            s3.object_keys(IN_MEMORY_BUCKET),
+            vec!["renamed.txt", "source.txt"]
+        );
+
+        let response = client
+            .execute_command(DeleteObject {
+                key: "source.txt",
+                version_id: None,
+            })
+            .await?;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(client.head_object("source.txt").await?.is_none());
+        assert!(client.get_object_tagging("source.txt").await?.is_none());
+        let Err(S3Error::Service(error)) = client.get_object("source.txt").await else {
+            panic!("Expected a service error");
+        };
+        assert_eq!(error.code, S3ErrorCode::NoSuchKey);
+        Ok(())
+    }
+    #[tokio::test]
+    async fn follows_region_redirect() -> anyhow::Result<()> {
+        init_test_logger();
+        let s3 = InMemoryS3::new().with_bucket("eu-bucket");
+        s3.set_bucket_region("eu-bucket", "eu-west-1");
+        s3.put_object("eu-bucket", "file.txt", "content");
+        let client = S3ClientBuilder::default()
+            .with_region(OfficialRegion::UsEast1)
+            .with_credentials(Default::default())
+            .with_transport(s3.clone())
+            .build()?;
+        let bucket = client.open_bucket("eu-bucket");
+        let response = bucket.get_object("file.txt").await?.expect("Object");
+        assert_eq!(response.0.text().await?, "content");
+        assert_eq!(s3.request_count(), 2);
+        assert_eq!(bucket.region().name(), "eu-west-1");
+        assert!(bucket.host()?.contains("eu-west-1"));
+
+        // The region is cached for every client of the bucket
+        let bucket = client.open_bucket("eu-bucket");
+        assert!(bucket.head_object("file.txt").await?.is_some());
+        assert_eq!(s3.request_count(), 3);
+        Ok(())
+    }
+    #[tokio::test]
+    async fn presign() -> anyhow::Result<()> {
+        init_test_logger();
+        let (s3, client) = create_in_memory_bucket_client();
+        s3.put_object(IN_MEMORY_BUCKET, "dir/test file.txt", "Hello, World!");
+        let get = GetObject {
+            key: "dir/test file.txt",
+            ..Default::default()
+        };
+        let url = client.presign(get, Duration::from_secs(300)).await?;
+        let query: Vec<_> = url.query_pairs().map(|(key, _)| key.into_owned()).collect();
+        assert_eq!(
+            query,
+            vec![
+                "X-Amz-Algorithm",
+                "X-Amz-Credential",
+                "X-Amz-Date",
+                "X-Amz-Expires",
+                "X-Amz-SignedHeaders",
+                "X-Amz-Signature"
+            ]
+        );
+        assert_eq!(url.path(), "/test-bucket/dir/test%20file.txt");
+        let response = s3.execute(s3.get(url).build()?).await?;
+        assert_eq!(response.text().await?, "Hello, World!");
+
+        let too_long = client
+            .presign(
+                GetObject {
+                    key: "dir/test file.txt",
+                    ..Default::default()
+                },
+                Duration::from_secs(8 * 24 * 60 * 60),
+            )
+            .await;
+        assert!(matches!(
+            too_long,
+            Err(S3Error::InvalidPresignExpiration(_))
+        ));
+        Ok(())
+    }
+    #[tokio::test]
+    async fn presign_post() -> anyhow::Result<()> {
+        init_test_logger();
+        let (_, client) = create_in_memory_bucket_client();
+        let policy = client
+            .post_policy()
+            .with_key("uploads/test.txt")
+            .with_expires_in(Duration::from_secs(300));
+        let post = client.presign_post(policy).await?;
+        assert_eq!(post.url.as_str(), "http://localhost:9000/test-bucket/");
+        assert_eq!(post.field("key"), Some("uploads/test.txt"));
+        assert!(post.field("policy").is_some());
+        assert!(post.field("x-amz-signature").is_some());
+        Ok(())
+    }
+}
@@ -5,11 +5,16 @@ use reqwest::{Client, ClientBuilder};
 use tracing::{debug, error};
 use tux_io_s3_types::{
     credentials::CredentialsVariants,
-    region::{RegionType, S3Implementation, S3Region},
+    region::{RegionType, S3Region},
 };
 
 use crate::{
-    client::{BucketClient, S3Client, S3ClientInner, settings::AccessType},
+    client::{
+        AwsEndpointResolver, BucketClient, Clock, ConcurrencyLimit, EndpointResolver, Interceptor,
+        MetricsSink, PathStyleEndpointResolver, RetryPolicy, S3Client, S3ClientInner, SystemClock,
+        http_client::HttpClient,
+        settings::{AccessType, EndpointVariants, ListObjectsVersion, PayloadSigning},
+    },
     credentials::provider::CredentialsProvider,
 };
 /// Runtime User Agent Value
@@ -23,6 +28,12 @@ const BUILT_IN_DEFAULT_USER_AGENT: HeaderValue =
 pub enum BuilderError {
     #[error("Missing region")]
     MissingRegion,
+    #[error(
+        "Missing transport. Custom transports must be set with `S3ClientBuilder::with_transport`"
+    )]
+    MissingTransport,
+    #[error("Invalid endpoint variants: {0}")]
+    InvalidEndpointVariants(&'static str),
     #[error(transparent)]
     HttpReqwestClientBuilderError(#[from] reqwest::Error),
 }
@@ -52,25 +63,55 @@ fn default_user_agent() -> HeaderValue {
         BUILT_IN_DEFAULT_USER_AGENT
     }
 }
+/// Builds a [S3Client] or a [BucketClient]
+///
+/// By default requests are sent with a [reqwest::Client] built from [S3ClientBuilder::http_client_builder].
+/// Any other [HttpClient] can be used with [S3ClientBuilder::with_transport]
+///
+/// Failed requests are retried with [RetryPolicy::default], up to 3 attempts per command.
+/// Use [S3ClientBuilder::with_retry_policy] with [RetryPolicy::disabled] to send every request once.
 #[derive(Debug)]
-pub struct S3ClientBuilder {
+pub struct S3ClientBuilder<Client: HttpClient = reqwest::Client> {
     region: Option<S3Region>,
     client_builder: reqwest::ClientBuilder,
+    transport: Option<Client>,
     access_type: Option<AccessType>,
     credentials: Option<Arc<CredentialsProvider>>,
+    retry_policy: RetryPolicy,
+    interceptors: Vec<Arc<dyn Interceptor>>,
+    clock: Arc<dyn Clock>,
+    metrics: Option<Arc<dyn MetricsSink>>,
+    concurrency_limit: ConcurrencyLimit,
+    endpoint_variants: EndpointVariants,
+    endpoint_resolver: Option<Arc<dyn EndpointResolver>>,
+    list_objects_version: ListObjectsVersion,
+    payload_signing: PayloadSigning,
+    unsigned_payload_over_http: bool,
 }
 impl Default for S3ClientBuilder {
     fn default() -> Self {
         Self {
             region: None,
             client_builder: Client::builder().user_agent(default_user_agent()),
+            transport: None,
             access_type: None,
             credentials: None,
+            retry_policy: RetryPolicy::default(),
+            interceptors: Vec::new(),
+            clock: Arc::new(SystemClock),
+            metrics: None,
+            concurrency_limit: ConcurrencyLimit::unlimited(),
+            endpoint_variants: EndpointVariants::default(),
+            endpoint_resolver: None,
+            list_objects_version: ListObjectsVersion::default(),
+            payload_signing: PayloadSigning::default(),
+            unsigned_payload_over_http: false,
         }
     }
 }
 
-impl S3ClientBuilder {
+impl<Client: HttpClient> S3ClientBuilder<Client> {
+    /// Changes the settings of the [reqwest::Client] used when no transport was set with [S3ClientBuilder::with_transport]
     pub fn http_client_builder<F>(mut self, builder: F) -> Self
     where
         F: FnOnce(ClientBuilder) -> ClientBuilder,
@@ -82,6 +123,26 @@ impl S3ClientBuilder {
         self.client_builder = client;
         self
     }
+    /// Sends all requests with the given [HttpClient] instead of building a [reqwest::Client]
+    pub fn with_transport<T: HttpClient>(self, transport: T) -> S3ClientBuilder<T> {
+        S3ClientBuilder {
+            region: self.region,
+            client_builder: self.client_builder,
+            transport: Some(transport),
+            access_type: self.access_type,
+            credentials: self.credentials,
+            retry_policy: self.retry_policy,
+            interceptors: self.interceptors,
+            clock: self.clock,
+            metrics: self.metrics,
+            concurrency_limit: self.concurrency_limit,
+            endpoint_variants: self.endpoint_variants,
+            endpoint_resolver: self.endpoint_resolver,
+            list_objects_version: self.list_objects_version,
+            payload_signing: self.payload_signing,
+            unsigned_payload_over_http: self.unsigned_payload_over_http,
+        }
+    }
     pub fn with_access_type(mut self, access_type: AccessType) -> Self {
         self.access_type = Some(access_type);
         self
@@ -94,8 +155,79 @@ impl S3ClientBuilder {
         self.region = Some(region.into());
         self
     }
-    fn inner_client(self) -> Result<Arc<S3ClientInner>, BuilderError> {
-        let client: Client = self.client_builder.build()?;
+    /// Sets how failed requests are retried.
+    ///
+    /// Defaults to [RetryPolicy::default], which makes up to 3 attempts. Use [RetryPolicy::disabled] to send every request once.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+    /// Adds an [Interceptor] that is called for every command.
+    ///
+    /// Interceptors are called in the order they are added.
+    pub fn with_interceptor(mut self, interceptor: impl Interceptor + 'static) -> Self {
+        self.interceptors.push(Arc::new(interceptor));
+        self
+    }
+    /// Sets the [Clock] requests are signed with.
+    ///
+    /// Defaults to [SystemClock]. A detected clock skew is applied on top of it.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+    /// Sets the [MetricsSink] that receives the metrics of every command.
+    pub fn with_metrics_sink(mut self, metrics: impl MetricsSink + 'static) -> Self {
+        self.metrics = Some(Arc::new(metrics));
+        self
+    }
+    /// Limits how many requests are in flight at the same time.
+    ///
+    /// Defaults to [ConcurrencyLimit::unlimited].
+    pub fn with_concurrency_limit(mut self, limit: ConcurrencyLimit) -> Self {
+        self.concurrency_limit = limit;
+        self
+    }
+    /// Sends requests to the dual-stack, FIPS or Transfer Acceleration endpoints of the region.
+    ///
+    /// Building fails if the variants can not be used with the region or access type.
+    pub fn with_endpoint_variants(mut self, endpoint_variants: EndpointVariants) -> Self {
+        self.endpoint_variants = endpoint_variants;
+        self
+    }
+    /// Sets the [EndpointResolver] that decides the url, `Host` header and signing region of every command.
+    ///
+    /// Replaces the resolver picked from [S3ClientBuilder::with_access_type] and [S3ClientBuilder::with_endpoint_variants].
+    pub fn with_endpoint_resolver(mut self, resolver: impl EndpointResolver + 'static) -> Self {
+        self.endpoint_resolver = Some(Arc::new(resolver));
+        self
+    }
+    /// Sets the ListObjects API used by [BucketClient::list_objects_stream].
+    ///
+    /// Defaults to [ListObjectsVersion::V2].
+    pub fn with_list_objects_version(mut self, version: ListObjectsVersion) -> Self {
+        self.list_objects_version = version;
+        self
+    }
+    /// Sets how request bodies are signed. Commands can override it with [crate::command::S3CommandBody::with_payload_signing].
+    ///
+    /// Defaults to [PayloadSigning::FullHash].
+    pub fn with_payload_signing(mut self, payload_signing: PayloadSigning) -> Self {
+        self.payload_signing = payload_signing;
+        self
+    }
+    /// Allows [PayloadSigning::Unsigned] for endpoints that are not HTTPS.
+    ///
+    /// Without TLS nothing protects the body from being changed.
+    pub fn with_unsigned_payload_over_http(mut self, allow: bool) -> Self {
+        self.unsigned_payload_over_http = allow;
+        self
+    }
+    fn inner_client(self) -> Result<Arc<S3ClientInner<Client>>, BuilderError> {
+        let client = match self.transport {
+            Some(transport) => transport,
+            None => Client::from_client_builder(self.client_builder)?,
+        };
         let credentials = if let Some(creds) = self.credentials {
             creds
         } else if let Some(credentials) = CredentialsVariants::load_from_environment() {
@@ -106,26 +238,58 @@ impl S3ClientBuilder {
         };
         let region = self.region.ok_or(BuilderError::MissingRegion)?;
 
-        let access_type = self
-            .access_type
-            .unwrap_or_else(|| match region.implementation() {
-                S3Implementation::AWS => AccessType::VirtualHostedStyle,
-                _ => AccessType::PathStyle,
-            });
+        let endpoint_resolver = match self.endpoint_resolver {
+            Some(resolver) => resolver,
+            None => {
+                let access_type = self.access_type.unwrap_or_else(|| {
+                    if region.supports_virtual_hosted_style() {
+                        AccessType::VirtualHostedStyle
+                    } else {
+                        AccessType::PathStyle
+                    }
+                });
+                if let Some(err) = self.endpoint_variants.validate(&region, access_type) {
+                    return Err(BuilderError::InvalidEndpointVariants(err));
+                }
+                let resolver: Arc<dyn EndpointResolver> = match access_type {
+                    AccessType::PathStyle => {
+                        Arc::new(PathStyleEndpointResolver::new(self.endpoint_variants))
+                    }
+                    AccessType::VirtualHostedStyle => {
+                        Arc::new(AwsEndpointResolver::new(self.endpoint_variants))
+                    }
+                };
+                resolver
+            }
+        };
 
         let inner = S3ClientInner {
             http_client: client,
             region,
-            access_type,
+            endpoint_resolver,
             credentials,
+            retry_policy: self.retry_policy,
+            interceptors: self.interceptors,
+            bucket_regions: Default::default(),
+            clock: self.clock,
+            clock_skew: Default::default(),
+            metrics: self.metrics,
+            limiter: self.concurrency_limit.into(),
+            list_objects_version: self.list_objects_version,
+            detected_list_objects_version: Default::default(),
+            payload_signing: self.payload_signing,
+            unsigned_payload_over_http: self.unsigned_payload_over_http,
         };
         Ok(Arc::new(inner))
     }
-    pub fn build(self) -> Result<S3Client, BuilderError> {
+    pub fn build(self) -> Result<S3Client<Client>, BuilderError> {
         let inner = self.inner_client()?;
         Ok(S3Client { client: inner })
     }
-    pub fn bucket_client(self, bucket: impl Into<String>) -> Result<BucketClient, BuilderError> {
+    pub fn bucket_client(
+        self,
+        bucket: impl Into<String>,
+    ) -> Result<BucketClient<Client>, BuilderError> {
         let inner = self.inner_client()?;
         let result = BucketClient {
             client: inner,
@@ -139,9 +303,12 @@ impl S3ClientBuilder {
 mod tests {
     use std::time::Duration;
 
-    use tux_io_s3_types::region::OfficialRegion;
+    use bytes::Bytes;
+    use http::{HeaderMap, StatusCode};
+    use tux_io_s3_types::region::{CustomRegion, OfficialRegion};
 
     use super::*;
+    use crate::client::http_client::{MockOkClient, MockResponse};
     #[test]
     fn test_s3_client_builder() {
         let builder = S3ClientBuilder::default()
@@ -152,4 +319,95 @@ mod tests {
 
         let _client = builder.build().unwrap();
     }
+    #[tokio::test]
+    async fn custom_transport() -> anyhow::Result<()> {
+        let transport = MockOkClient::new(MockResponse {
+            body: Bytes::from_static(
+                b"<Tagging><TagSet><Tag><Key>key</Key><Value>value</Value></Tag></TagSet></Tagging>",
+            ),
+            status: StatusCode::OK,
+            headers: HeaderMap::new(),
+        });
+        let client = S3ClientBuilder::default()
+            .with_region(OfficialRegion::UsEast1)
+            .with_credentials(CredentialsProvider::default().into())
+            .with_transport(transport.clone())
+            .bucket_client("bucket")?;
+        let tagging = client
+            .get_object_tagging("test.txt")
+            .await?
+            .expect("Expected a tagging set");
+        assert!(tagging.has_tag("key"));
+        assert_eq!(transport.request_count(), 1);
+        Ok(())
+    }
+    fn variants_builder(region: OfficialRegion, variants: EndpointVariants) -> S3ClientBuilder {
+        S3ClientBuilder::default()
+            .with_region(region)
+            .with_credentials(CredentialsProvider::default().into())
+            .with_endpoint_variants(variants)
+    }
+    #[test]
+    fn endpoint_variants() -> anyhow::Result<()> {
+        let dual_stack = EndpointVariants::default().with_dual_stack(true);
+        let client = variants_builder(OfficialRegion::UsEast1, dual_stack).build()?;
+        assert_eq!(client.host()?, "s3.dualstack.us-east-1.amazonaws.com");
+        let bucket = client.open_bucket("bucket");
+        assert_eq!(
+            bucket.url()?.as_str(),
+            "https://bucket.s3.dualstack.us-east-1.amazonaws.com/"
+        );
+
+        let bucket = variants_builder(OfficialRegion::CnNorth1, dual_stack)
+            .with_access_type(AccessType::PathStyle)
+            .bucket_client("bucket")?;
+        assert_eq!(
+            bucket.url()?.as_str(),
+            "https://s3.dualstack.cn-north-1.amazonaws.com.cn/bucket/"
+        );
+        assert_eq!(bucket.host()?, "s3.dualstack.cn-north-1.amazonaws.com.cn");
+
+        let fips = dual_stack.with_fips(true);
+        let bucket = variants_builder(OfficialRegion::UsGovWest1, fips).bucket_client("bucket")?;
+        assert_eq!(
+            bucket.host()?,
+            "bucket.s3-fips.dualstack.us-gov-west-1.amazonaws.com"
+        );
+
+        let accelerate = EndpointVariants::default().with_accelerate(true);
+        let bucket =
+            variants_builder(OfficialRegion::EuWest1, accelerate).bucket_client("bucket")?;
+        assert_eq!(bucket.host()?, "bucket.s3-accelerate.amazonaws.com");
+        assert_eq!(bucket.region(), S3Region::Official(OfficialRegion::EuWest1));
+        Ok(())
+    }
+    #[test]
+    fn invalid_endpoint_variants() {
+        let accelerate = EndpointVariants::default().with_accelerate(true);
+        let path_style = variants_builder(OfficialRegion::EuWest1, accelerate)
+            .with_access_type(AccessType::PathStyle)
+            .build();
+        assert!(matches!(
+            path_style,
+            Err(BuilderError::InvalidEndpointVariants(_))
+        ));
+        let fips = variants_builder(OfficialRegion::UsEast1, accelerate.with_fips(true)).build();
+        assert!(matches!(
+            fips,
+            Err(BuilderError::InvalidEndpointVariants(_))
+        ));
+        let china = variants_builder(OfficialRegion::CnNorth1, accelerate).build();
+        assert!(matches!(
+            china,
+            Err(BuilderError::InvalidEndpointVariants(_))
+        ));
+        let custom = S3ClientBuilder::default()
+            .with_region("http://localhost:9000".parse::<CustomRegion>().unwrap())
+            .with_endpoint_variants(EndpointVariants::default().with_dual_stack(true))
+            .build();
+        assert!(matches!(
+            custom,
+            Err(BuilderError::InvalidEndpointVariants(_))
+        ));
+    }
 }
@@ -0,0 +1,25 @@
+use std::fmt::Debug;
+
+use chrono::{DateTime, Utc};
+/// Source of the time requests are signed with.
+///
+/// Set with [crate::client::S3ClientBuilder::with_clock]. Defaults to [SystemClock]
+pub trait Clock: Debug + Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+/// Uses the time of the system
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+/// Always returns the same time. Useful for deterministic signatures in tests.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub DateTime<Utc>);
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
@@ -0,0 +1,393 @@
+use std::{borrow::Cow, fmt::Debug, net::Ipv4Addr};
+
+use tux_io_s3_types::region::{RegionType, S3Region};
+use url::Url;
+
+use crate::{S3Error, client::settings::EndpointVariants};
+/// What a command is sent to.
+#[derive(Debug, Clone, Copy)]
+pub struct EndpointParams<'request> {
+    /// The region of the bucket or the client
+    pub region: &'request S3Region,
+    /// None for account commands such as `ListBuckets`
+    pub bucket: Option<&'request str>,
+    /// [crate::command::CommandType::name] of the command, the S3 operation such as `GetObject`.
+    /// None when only the url is requested.
+    pub operation: Option<&'static str>,
+}
+/// Where a command is sent and which region it is signed for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedEndpoint {
+    /// The base url. Commands append the key and query to it so the path must end with `/`.
+    pub url: Url,
+    /// Value of the `Host` header
+    pub host: String,
+    pub signing_region: S3Region,
+}
+impl ResolvedEndpoint {
+    /// Builds the `Host` header from the url. The port is only included if it is not the default port of the scheme.
+    pub fn new(mut url: Url, signing_region: S3Region) -> Result<Self, S3Error> {
+        let Some(host_str) = url.host_str() else {
+            return Err(S3Error::Endpoint(
+                format!("Url {url} does not have a host").into(),
+            ));
+        };
+        let host = match url.port() {
+            Some(port) => format!("{host_str}:{port}"),
+            None => host_str.to_owned(),
+        };
+        if !url.path().ends_with('/') {
+            url.set_path(&format!("{}/", url.path()));
+        }
+        Ok(Self {
+            url,
+            host,
+            signing_region,
+        })
+    }
+}
+/// Decides the url, `Host` header and signing region of every command.
+///
+/// Set with [crate::client::S3ClientBuilder::with_endpoint_resolver].
+/// Defaults to [AwsEndpointResolver] or [PathStyleEndpointResolver] depending on [crate::client::AccessType].
+pub trait EndpointResolver: Debug + Send + Sync {
+    fn resolve(&self, params: &EndpointParams<'_>) -> Result<ResolvedEndpoint, S3Error>;
+}
+impl<T: EndpointResolver + ?Sized> EndpointResolver for std::sync::Arc<T> {
+    fn resolve(&self, params: &EndpointParams<'_>) -> Result<ResolvedEndpoint, S3Error> {
+        (**self).resolve(params)
+    }
+}
+/// Returns the endpoint of the region with the variants applied
+///
+/// Requests without a bucket use the regional endpoint instead of Transfer Acceleration.
+fn region_url(
+    region: &S3Region,
+    variants: &EndpointVariants,
+    bucket: Option<&str>,
+) -> Result<Url, S3Error> {
+    let mut url = region.endpoint_url();
+    let variants = match bucket {
+        Some(_) => *variants,
+        None => variants.without_bucket(),
+    };
+    if let S3Region::Official(official) = region
+        && !variants.is_default()
+    {
+        url.set_host(Some(&variants.host(official)))?;
+    }
+    Ok(url)
+}
+fn path_style(region: &S3Region, mut url: Url, bucket: &str) -> Result<ResolvedEndpoint, S3Error> {
+    url.set_path(&format!("/{bucket}/"));
+    ResolvedEndpoint::new(url, region.clone())
+}
+/// Returns true if the bucket can be used as a subdomain of the endpoint.
+///
+/// Buckets with dots only work over http because they do not match the TLS certificate of the endpoint.
+pub fn is_virtual_hostable_bucket(bucket: &str, scheme: &str) -> bool {
+    let valid_length = (3..=63).contains(&bucket.len());
+    let valid_chars = bucket
+        .bytes()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == b'-' || c == b'.');
+    let valid_edges = bucket
+        .bytes()
+        .next()
+        .zip(bucket.bytes().last())
+        .is_some_and(|(first, last)| first.is_ascii_alphanumeric() && last.is_ascii_alphanumeric());
+    let valid_labels = !bucket.contains("..") && !bucket.contains(".-") && !bucket.contains("-.");
+    let is_ip = bucket.parse::<Ipv4Addr>().is_ok();
+    let dots_allowed = scheme == "http" || !bucket.contains('.');
+    valid_length && valid_chars && valid_edges && valid_labels && !is_ip && dots_allowed
+}
+/// The AWS addressing rules.
+///
+/// Buckets are addressed as `{bucket}.{endpoint}`. Buckets that can not be used in a hostname fall back to path style.
+#[derive(Debug, Clone, Default)]
+pub struct AwsEndpointResolver {
+    pub variants: EndpointVariants,
+}
+impl AwsEndpointResolver {
+    pub fn new(variants: EndpointVariants) -> Self {
+        Self { variants }
+    }
+}
+impl EndpointResolver for AwsEndpointResolver {
+    fn resolve(&self, params: &EndpointParams<'_>) -> Result<ResolvedEndpoint, S3Error> {
+        let mut url = region_url(params.region, &self.variants, params.bucket)?;
+        let Some(bucket) = params.bucket else {
+            return ResolvedEndpoint::new(url, params.region.clone());
+        };
+        if !is_virtual_hostable_bucket(bucket, url.scheme()) {
+            if self.variants.accelerate {
+                return Err(S3Error::Endpoint(
+                    format!("Bucket {bucket} can not be used with Transfer Acceleration").into(),
+                ));
+            }
+            return path_style(params.region, url, bucket);
+        }
+        let url = match params.region {
+            S3Region::Custom(custom) => custom.bucket_url(bucket)?,
+            S3Region::Official(_) => {
+                let host = format!("{bucket}.{}", url.host_str().unwrap_or_default());
+                url.set_host(Some(&host))?;
+                url.set_path("/");
+                url
+            }
+        };
+        ResolvedEndpoint::new(url, params.region.clone())
+    }
+}
+/// Addresses buckets as `{endpoint}/{bucket}/`
+#[derive(Debug, Clone, Default)]
+pub struct PathStyleEndpointResolver {
+    pub variants: EndpointVariants,
+}
+impl PathStyleEndpointResolver {
+    pub fn new(variants: EndpointVariants) -> Self {
+        Self { variants }
+    }
+}
+impl EndpointResolver for PathStyleEndpointResolver {
+    fn resolve(&self, params: &EndpointParams<'_>) -> Result<ResolvedEndpoint, S3Error> {
+        let url = region_url(params.region, &self.variants, params.bucket)?;
+        match params.bucket {
+            Some(bucket) => path_style(params.region, url, bucket),
+            None => ResolvedEndpoint::new(url, params.region.clone()),
+        }
+    }
+}
+/// Builds the url from a template such as `https://{bucket}.storage.example.com`.
+///
+/// `{bucket}` and `{region}` are replaced with the bucket and region name.
+/// Account commands use the template with `{bucket}.` and `/{bucket}` removed unless [TemplateEndpointResolver::with_account_template] is set.
+#[derive(Debug, Clone)]
+pub struct TemplateEndpointResolver {
+    template: String,
+    account_template: String,
+}
+impl TemplateEndpointResolver {
+    /// Fails if the template does not produce a valid url.
+    pub fn new(template: impl Into<String>) -> Result<Self, url::ParseError> {
+        let template = template.into();
+        let account_template = template.replace("{bucket}.", "").replace("/{bucket}", "");
+        let resolver = Self {
+            template,
+            account_template,
+        };
+        resolver.render(&resolver.template, Some("bucket"), "region")?;
+        resolver.render(&resolver.account_template, None, "region")?;
+        Ok(resolver)
+    }
+    /// Fails if the template does not produce a valid url.
+    pub fn with_account_template(
+        mut self,
+        account_template: impl Into<String>,
+    ) -> Result<Self, url::ParseError> {
+        self.account_template = account_template.into();
+        self.render(&self.account_template, None, "region")?;
+        Ok(self)
+    }
+    fn render(
+        &self,
+        template: &str,
+        bucket: Option<&str>,
+        region: &str,
+    ) -> Result<Url, url::ParseError> {
+        let mut url = Cow::Borrowed(template);
+        if let Some(bucket) = bucket {
+            url = Cow::Owned(url.replace("{bucket}", bucket));
+        }
+        if url.contains("{region}") {
+            url = Cow::Owned(url.replace("{region}", region));
+        }
+        Url::parse(&url)
+    }
+}
+impl EndpointResolver for TemplateEndpointResolver {
+    fn resolve(&self, params: &EndpointParams<'_>) -> Result<ResolvedEndpoint, S3Error> {
+        let template = match params.bucket {
+            Some(_) => &self.template,
+            None => &self.account_template,
+        };
+        let url = self.render(template, params.bucket, params.region.name())?;
+        ResolvedEndpoint::new(url, params.region.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tux_io_s3_types::region::{CustomRegion, OfficialRegion};
+
+    use super::*;
+    use std::sync::Arc;
+
+    use tux_io_s3_types::credentials::Credentials;
+
+    use crate::{
+        client::{S3ClientBuilder, http_client::InMemoryS3},
+        command::head::HeadBucket,
+        test::{IN_MEMORY_BUCKET, in_memory_client_builder},
+    };
+
+    fn resolve(
+        resolver: &impl EndpointResolver,
+        region: impl Into<S3Region>,
+        bucket: Option<&str>,
+    ) -> ResolvedEndpoint {
+        resolver
+            .resolve(&EndpointParams {
+                region: &region.into(),
+                bucket,
+                operation: None,
+            })
+            .unwrap()
+    }
+    fn custom(endpoint: &str) -> CustomRegion {
+        endpoint.parse().unwrap()
+    }
+    #[test]
+    fn aws_rules() {
+        let resolver = AwsEndpointResolver::default();
+        let endpoint = resolve(&resolver, OfficialRegion::EuWest1, Some("bucket"));
+        assert_eq!(
+            endpoint.url.as_str(),
+            "https://bucket.s3.eu-west-1.amazonaws.com/"
+        );
+        assert_eq!(endpoint.host, "bucket.s3.eu-west-1.amazonaws.com");
+        assert_eq!(
+            endpoint.signing_region,
+            S3Region::Official(OfficialRegion::EuWest1)
+        );
+
+        let endpoint = resolve(&resolver, OfficialRegion::EuWest1, Some("my.bucket"));
+        assert_eq!(
+            endpoint.url.as_str(),
+            "https://s3.eu-west-1.amazonaws.com/my.bucket/"
+        );
+        assert_eq!(endpoint.host, "s3.eu-west-1.amazonaws.com");
+
+        let endpoint = resolve(
+            &resolver,
+            custom("http://localhost:9000"),
+            Some("my.bucket"),
+        );
+        assert_eq!(endpoint.url.as_str(), "http://my.bucket.localhost:9000/");
+        assert_eq!(endpoint.host, "my.bucket.localhost:9000");
+
+        let endpoint = resolve(&resolver, OfficialRegion::UsEast1, None);
+        assert_eq!(endpoint.url.as_str(), "https://s3.amazonaws.com/");
+        assert_eq!(endpoint.host, "s3.amazonaws.com");
+    }
+    #[test]
+    fn path_style_rules() {
+        let resolver = PathStyleEndpointResolver::default();
+        let endpoint = resolve(
+            &resolver,
+            custom("https://minio.local:9443"),
+            Some("bucket"),
+        );
+        assert_eq!(endpoint.url.as_str(), "https://minio.local:9443/bucket/");
+        assert_eq!(endpoint.host, "minio.local:9443");
+
+        let endpoint = resolve(&resolver, custom("https://minio.local:443"), Some("bucket"));
+        assert_eq!(endpoint.host, "minio.local");
+    }
+    #[test]
+    fn template_rules() {
+        let resolver = TemplateEndpointResolver::new("https://{bucket}.{region}.example.com")
+            .expect("Valid template");
+        let region = custom("https://example.com").with_name("west");
+        let endpoint = resolve(&resolver, region.clone(), Some("bucket"));
+        assert_eq!(endpoint.host, "bucket.west.example.com");
+        let endpoint = resolve(&resolver, region.clone(), None);
+        assert_eq!(endpoint.host, "west.example.com");
+
+        let resolver = TemplateEndpointResolver::new("http://storage:8080/{bucket}").unwrap();
+        let endpoint = resolve(&resolver, region, Some("bucket"));
+        assert_eq!(endpoint.url.as_str(), "http://storage:8080/bucket/");
+        assert_eq!(endpoint.host, "storage:8080");
+
+        assert!(TemplateEndpointResolver::new("not a url/{bucket}").is_err());
+    }
+    #[tokio::test]
+    async fn template_resolver_client() -> anyhow::Result<()> {
+        let s3 = InMemoryS3::new().with_bucket(IN_MEMORY_BUCKET);
+        s3.put_object(IN_MEMORY_BUCKET, "test.txt", "content");
+        let resolver = TemplateEndpointResolver::new("http://{bucket}.storage.local:8080")?;
+        let client = in_memory_client_builder(&s3)
+            .with_endpoint_resolver(resolver)
+            .bucket_client(IN_MEMORY_BUCKET)?;
+        assert_eq!(client.host()?, "test-bucket.storage.local:8080");
+        assert!(client.head_object("test.txt").await?.is_some());
+        Ok(())
+    }
+    /// Records the operation of every resolved endpoint
+    #[derive(Debug, Default)]
+    struct RecordOperations(std::sync::Mutex<Vec<Option<&'static str>>>);
+    impl EndpointResolver for RecordOperations {
+        fn resolve(&self, params: &EndpointParams<'_>) -> Result<ResolvedEndpoint, S3Error> {
+            self.0.lock().unwrap().push(params.operation);
+            PathStyleEndpointResolver::default().resolve(params)
+        }
+    }
+    #[tokio::test]
+    async fn operation_names() -> anyhow::Result<()> {
+        let s3 = InMemoryS3::new().with_bucket(IN_MEMORY_BUCKET);
+        s3.put_object(IN_MEMORY_BUCKET, "test.txt", "content");
+        let resolver = Arc::new(RecordOperations::default());
+        let client = in_memory_client_builder(&s3)
+            .with_endpoint_resolver(resolver.clone())
+            .bucket_client(IN_MEMORY_BUCKET)?;
+        client.execute_command(HeadBucket).await?;
+        client.head_object("test.txt").await?;
+        client.get_object("test.txt").await?;
+        assert_eq!(
+            *resolver.0.lock().unwrap(),
+            vec![Some("HeadBucket"), Some("HeadObject"), Some("GetObject")]
+        );
+        Ok(())
+    }
+    #[tokio::test]
+    async fn virtual_hosted_custom_region() -> anyhow::Result<()> {
+        #[derive(serde::Deserialize)]
+        struct Config {
+            region: S3Region,
+        }
+        let config: Config = toml::from_str(
+            r#"region = { endpoint = "http://localhost:9000", virtual_hosted = true }"#,
+        )?;
+        assert!(config.region.supports_virtual_hosted_style());
+        let s3 = InMemoryS3::new().with_bucket(IN_MEMORY_BUCKET);
+        s3.put_object(IN_MEMORY_BUCKET, "test.txt", "content");
+        let client = S3ClientBuilder::default()
+            .with_region(config.region)
+            .with_credentials(Arc::new(Credentials::default().into()))
+            .with_transport(s3.clone())
+            .bucket_client(IN_MEMORY_BUCKET)?;
+        assert_eq!(client.url()?.as_str(), "http://test-bucket.localhost:9000/");
+        assert_eq!(client.host()?, "test-bucket.localhost:9000");
+        assert!(client.head_object("test.txt").await?.is_some());
+
+        let path_style = S3ClientBuilder::default()
+            .with_region(custom("http://localhost:9000"))
+            .with_credentials(Arc::new(Credentials::default().into()))
+            .bucket_client(IN_MEMORY_BUCKET)?;
+        assert_eq!(
+            path_style.url()?.as_str(),
+            "http://localhost:9000/test-bucket/"
+        );
+        Ok(())
+    }
+    #[test]
+    fn virtual_hostable_buckets() {
+        assert!(is_virtual_hostable_bucket("bucket-1", "https"));
+        assert!(!is_virtual_hostable_bucket("my.bucket", "https"));
+        assert!(is_virtual_hostable_bucket("my.bucket", "http"));
+        assert!(!is_virtual_hostable_bucket("Bucket", "https"));
+        assert!(!is_virtual_hostable_bucket("my_bucket", "https"));
+        assert!(!is_virtual_hostable_bucket("-bucket", "https"));
+        assert!(!is_virtual_hostable_bucket("192.168.1.1", "http"));
+        assert!(!is_virtual_hostable_bucket("ab", "https"));
+    }
+}
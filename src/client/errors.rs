@@ -1,4 +1,10 @@
-use std::{error::Error, fmt::Display};
+use std::{collections::HashMap, error::Error, fmt::Display};
+
+use http::StatusCode;
+use tux_io_s3_types::{
+    error::{Error as ErrorBody, S3ErrorCode},
+    headers::X_AMZ_REQUEST_ID,
+};
 #[derive(Debug)]
 pub enum HttpResponseError {
     Response(reqwest::Response),
@@ -42,3 +48,134 @@ impl Error for HttpResponseError {
         }
     }
 }
+/// An error returned by S3 in the `<Error>` body of a response.
+#[derive(Debug, Clone)]
+pub struct S3ServiceError {
+    pub status: StatusCode,
+    pub code: S3ErrorCode,
+    pub message: Option<String>,
+    pub request_id: Option<String>,
+    pub host_id: Option<String>,
+    /// Any other elements in the error body. Such as `Key`, `BucketName` or `Resource`
+    pub attributes: HashMap<String, String>,
+}
+impl S3ServiceError {
+    pub fn new(status: StatusCode, body: ErrorBody) -> Self {
+        Self {
+            status,
+            code: body.error_code(),
+            message: body.message,
+            request_id: body.request_id,
+            host_id: body.host_id,
+            attributes: body.attributes,
+        }
+    }
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(String::as_str)
+    }
+}
+impl Display for S3ServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "S3 Error: {} Status Code: {}", self.code, self.status)?;
+        if let Some(message) = &self.message {
+            write!(f, " Message: {}", message)?;
+        }
+        if let Some(request_id) = &self.request_id {
+            write!(f, " Request ID: {}", request_id)?;
+        }
+        Ok(())
+    }
+}
+impl Error for S3ServiceError {}
+/// Reads the body of a failed response and converts it into a [S3ServiceError].
+///
+/// If the body is not an S3 `<Error>` document the response is returned in a [HttpResponseError::Response]
+pub(crate) async fn service_error_from_response(
+    response: reqwest::Response,
+) -> Result<S3ServiceError, HttpResponseError> {
+    let status = response.status();
+    let request_id = response
+        .headers()
+        .get(X_AMZ_REQUEST_ID)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_owned);
+    let (response, error_body) = buffer_error_response(response).await?;
+    let Some(error_body) = error_body else {
+        return Err(HttpResponseError::Response(response));
+    };
+    let mut error = S3ServiceError::new(status, error_body);
+    if error.request_id.is_none() {
+        error.request_id = request_id;
+    }
+    Ok(error)
+}
+/// Reads the body of a failed response so the `<Error>` document can be inspected.
+///
+/// Returns a new response with the same status, headers and body so callers can still read it.
+pub(crate) async fn buffer_error_response(
+    response: reqwest::Response,
+) -> Result<(reqwest::Response, Option<ErrorBody>), reqwest::Error> {
+    let status = response.status();
+    let version = response.version();
+    let headers = response.headers().clone();
+    let body = response.bytes().await?;
+    let error_body = if body.is_empty() {
+        None
+    } else {
+        quick_xml::de::from_reader(body.as_ref()).ok()
+    };
+    let mut rebuilt = http::Response::new(body);
+    *rebuilt.status_mut() = status;
+    *rebuilt.version_mut() = version;
+    *rebuilt.headers_mut() = headers;
+    Ok((reqwest::Response::from(rebuilt), error_body))
+}
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use http::StatusCode;
+    use tux_io_s3_types::error::S3ErrorCode;
+
+    use crate::S3Error;
+
+    fn response(status: StatusCode, body: &'static str) -> reqwest::Response {
+        let response = http::Response::builder()
+            .status(status)
+            .header("x-amz-request-id", "header-request-id")
+            .body(Bytes::from_static(body.as_bytes()))
+            .unwrap();
+        reqwest::Response::from(response)
+    }
+    #[tokio::test]
+    async fn parses_service_error() {
+        let error = S3Error::from_response(response(
+            StatusCode::NOT_FOUND,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<Error>
+    <Code>NoSuchKey</Code>
+    <Message>The specified key does not exist.</Message>
+    <Key>test.txt</Key>
+    <RequestId>4442587FB7D0A2F9</RequestId>
+</Error>"#,
+        ))
+        .await;
+        let S3Error::Service(service_error) = &error else {
+            panic!("Expected a service error got {error:?}");
+        };
+        assert_eq!(service_error.code, S3ErrorCode::NoSuchKey);
+        assert_eq!(service_error.status, StatusCode::NOT_FOUND);
+        assert_eq!(
+            service_error.request_id.as_deref(),
+            Some("4442587FB7D0A2F9")
+        );
+        assert_eq!(service_error.attribute("Key"), Some("test.txt"));
+        assert_eq!(error.error_code(), Some(&S3ErrorCode::NoSuchKey));
+        assert_eq!(error.status_code(), Some(StatusCode::NOT_FOUND));
+    }
+    #[tokio::test]
+    async fn falls_back_to_http_error() {
+        let error = S3Error::from_response(response(StatusCode::BAD_GATEWAY, "Bad Gateway")).await;
+        assert!(matches!(error, S3Error::HttpError(_)));
+        assert_eq!(error.status_code(), Some(StatusCode::BAD_GATEWAY));
+    }
+}
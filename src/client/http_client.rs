@@ -2,10 +2,24 @@ use std::{fmt::Debug, sync::Arc};
 
 use bytes::Bytes;
 use http::StatusCode;
-use reqwest::{Error, Request, RequestBuilder, Response};
+use reqwest::{ClientBuilder, Error, Request, RequestBuilder, Response};
 use url::Url;
 
-pub trait HttpClient: Send + Sync + Debug + Clone {
+use crate::client::BuilderError;
+#[cfg(any(test, feature = "testing"))]
+mod in_memory;
+#[cfg(any(test, feature = "testing"))]
+pub use in_memory::*;
+/// The transport used to send requests.
+///
+/// Implemented for [reqwest::Client]. Set a custom one with [crate::client::S3ClientBuilder::with_transport]
+pub trait HttpClient: Send + Sync + Debug + Clone + 'static {
+    /// Creates the transport from the HTTP settings of [crate::client::S3ClientBuilder].
+    ///
+    /// Transports that can not be built from a [ClientBuilder] must be set with [crate::client::S3ClientBuilder::with_transport]
+    fn from_client_builder(_builder: ClientBuilder) -> Result<Self, BuilderError> {
+        Err(BuilderError::MissingTransport)
+    }
     fn get(&self, url: Url) -> RequestBuilder {
         self.request(reqwest::Method::GET, url)
     }
@@ -28,6 +42,9 @@ pub trait HttpClient: Send + Sync + Debug + Clone {
 }
 
 impl HttpClient for reqwest::Client {
+    fn from_client_builder(builder: ClientBuilder) -> Result<Self, BuilderError> {
+        Ok(builder.build()?)
+    }
     fn request(&self, method: reqwest::Method, url: Url) -> RequestBuilder {
         self.request(method, url)
     }
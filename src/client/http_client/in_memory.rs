@@ -0,0 +1,1496 @@
+use std::{
+    collections::BTreeMap,
+    pin::Pin,
+    sync::{
+        Arc, Mutex, MutexGuard, PoisonError,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use ahash::AHashMap;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use bytes::{Bytes, BytesMut};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use http::{
+    HeaderMap, HeaderValue, Method, StatusCode,
+    header::{
+        AUTHORIZATION, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, DATE, ETAG,
+        IF_NONE_MATCH, LAST_MODIFIED, RANGE,
+    },
+};
+use http_body::Body as _;
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, utf8_percent_encode};
+use reqwest::{Error, Request, RequestBuilder, Response};
+use serde::Serialize;
+use tux_io_s3_types::{
+    checksum::ChecksumAlgorithm,
+    delete::{Delete, DeleteResult, DeletedObject},
+    error::{Error as ErrorBody, S3ErrorCode},
+    headers::{
+        CONTENT_MD5, X_AMZ_BUCKET_REGION, X_AMZ_CHECKSUM_ALGORITHM, X_AMZ_CHECKSUM_MODE,
+        X_AMZ_CHECKSUM_TYPE, X_AMZ_CONTENT_SHA256, X_AMZ_COPY_SOURCE, X_AMZ_DATE,
+        X_AMZ_DECODED_CONTENT_LENGTH, X_AMZ_RENAME_SOURCE, X_AMZ_REQUEST_ID, X_AMZ_TAGGING,
+    },
+    list::{
+        EncodingType,
+        buckets::{Bucket, ListAllMyBuckets},
+        object::{Object, StorageClass},
+        prefix::CommonPrefixes,
+        v1, v2,
+    },
+    multi_part::{
+        CompleteMultipartUpload, InitiateMultipartUploadResult, ListPartsResult, UploadedPart,
+    },
+    tag::{OwnedTag, OwnedTaggingSet},
+};
+use url::Url;
+
+use crate::{
+    client::{http_client::HttpClient, inner::MAX_CLOCK_SKEW},
+    utils::{LONG_DATE_FORMAT, checksum},
+};
+
+type S3Response = http::Response<Bytes>;
+/// A stateful S3 server that lives in memory.
+///
+/// Decodes path-style and virtual-hosted-style requests and keeps buckets, objects, tags and multipart uploads in memory.
+/// Responses use the same XML documents as S3 so every command can be tested without a server.
+///
+/// Signatures are not verified. Virtual-hosted-style requests are only detected for buckets that exist.
+///
+/// Only available with the `testing` feature.
+#[derive(Debug, Clone)]
+pub struct InMemoryS3 {
+    state: Arc<Mutex<InMemoryState>>,
+    request_counter: Arc<AtomicUsize>,
+    client: reqwest::Client,
+}
+/// An object stored by [InMemoryS3]
+#[derive(Debug, Clone, PartialEq)]
+pub struct InMemoryObject {
+    pub content: Bytes,
+    pub content_type: String,
+    pub etag: String,
+    pub last_modified: DateTime<Utc>,
+    /// All `x-amz-meta-*` headers sent with the object
+    pub metadata: HeaderMap,
+    pub tags: Vec<OwnedTag>,
+    /// Checksum sent with the upload. Objects of multipart uploads have a `-{parts}` suffix.
+    pub checksum: Option<(ChecksumAlgorithm, String)>,
+}
+#[derive(Debug)]
+struct InMemoryState {
+    buckets: BTreeMap<String, InMemoryBucket>,
+    next_upload_id: u64,
+    /// False if ListObjectsV2 responds with NotImplemented
+    list_objects_v2: bool,
+}
+impl Default for InMemoryState {
+    fn default() -> Self {
+        Self {
+            buckets: BTreeMap::new(),
+            next_upload_id: 0,
+            list_objects_v2: true,
+        }
+    }
+}
+#[derive(Debug)]
+struct InMemoryBucket {
+    creation_date: DateTime<Utc>,
+    /// Requests signed for any other region are redirected
+    region: Option<String>,
+    objects: BTreeMap<String, InMemoryObject>,
+    uploads: AHashMap<String, InMemoryUpload>,
+}
+impl Default for InMemoryBucket {
+    fn default() -> Self {
+        Self {
+            creation_date: Utc::now(),
+            region: None,
+            objects: BTreeMap::new(),
+            uploads: AHashMap::new(),
+        }
+    }
+}
+#[derive(Debug)]
+struct InMemoryUpload {
+    key: String,
+    content_type: String,
+    metadata: HeaderMap,
+    tags: Vec<OwnedTag>,
+    /// Every part has a checksum of this algorithm
+    checksum_algorithm: Option<ChecksumAlgorithm>,
+    parts: BTreeMap<u32, InMemoryPart>,
+}
+#[derive(Debug)]
+struct InMemoryPart {
+    etag: String,
+    content: Bytes,
+    last_modified: DateTime<Utc>,
+    checksum: Option<(ChecksumAlgorithm, String)>,
+}
+/// A decoded S3 request
+#[derive(Debug)]
+struct InMemoryRequest {
+    method: Method,
+    bucket: Option<String>,
+    key: Option<String>,
+    query: AHashMap<String, String>,
+    headers: HeaderMap,
+    body: Bytes,
+}
+impl InMemoryRequest {
+    /// Verifies the `x-amz-checksum-*` header or trailer against the body
+    fn checksum(&self) -> Result<Option<(ChecksumAlgorithm, String)>, InMemoryError> {
+        let Some((algorithm, expected)) = ChecksumAlgorithm::ALL
+            .into_iter()
+            .find_map(|algorithm| Some((algorithm, self.header(algorithm.header_name())?)))
+        else {
+            return Ok(None);
+        };
+        let calculated = checksum::checksum(algorithm, &self.body);
+        if calculated != expected {
+            return Err(InMemoryError::new(
+                StatusCode::BAD_REQUEST,
+                S3ErrorCode::BadDigest,
+                format!("The {algorithm} you specified did not match the calculated checksum."),
+            ));
+        }
+        Ok(Some((algorithm, calculated)))
+    }
+    /// Rejects requests with a `Content-MD5` that does not match the body
+    fn check_content_md5(&self) -> Result<(), InMemoryError> {
+        let Some(content_md5) = self.header(CONTENT_MD5) else {
+            return Ok(());
+        };
+        if STANDARD
+            .decode(content_md5)
+            .map_or(true, |digest| digest.len() != 16)
+        {
+            return Err(InMemoryError::new(
+                StatusCode::BAD_REQUEST,
+                S3ErrorCode::InvalidDigest,
+                "The Content-MD5 you specified was invalid.",
+            ));
+        }
+        if STANDARD.encode(md5::compute(&self.body).0) != content_md5 {
+            return Err(InMemoryError::new(
+                StatusCode::BAD_REQUEST,
+                S3ErrorCode::BadDigest,
+                "The Content-MD5 you specified did not match what we received.",
+            ));
+        }
+        Ok(())
+    }
+    /// Rejects requests signed more than 15 minutes away from the current time
+    fn check_request_time(&self) -> Result<(), InMemoryError> {
+        let Some(signed_at) = self
+            .header(X_AMZ_DATE)
+            .and_then(|value| NaiveDateTime::parse_from_str(value, LONG_DATE_FORMAT).ok())
+        else {
+            return Ok(());
+        };
+        let skew = Utc::now() - signed_at.and_utc();
+        if skew.abs() > MAX_CLOCK_SKEW {
+            return Err(InMemoryError::new(
+                StatusCode::FORBIDDEN,
+                S3ErrorCode::RequestTimeTooSkewed,
+                "The difference between the request time and the current time is too large.",
+            ));
+        }
+        Ok(())
+    }
+    /// Rejects expired presigned urls and removes the `X-Amz-*` query parameters of the signature
+    fn check_presigned_url(&mut self) -> Result<(), InMemoryError> {
+        if !self.has_query("X-Amz-Signature") {
+            return Ok(());
+        }
+        let signed_at = self
+            .query("X-Amz-Date")
+            .and_then(|value| NaiveDateTime::parse_from_str(value, LONG_DATE_FORMAT).ok());
+        let expires_in = self
+            .query("X-Amz-Expires")
+            .and_then(|value| value.parse::<i64>().ok());
+        let (Some(signed_at), Some(expires_in)) = (signed_at, expires_in) else {
+            return Err(InMemoryError::new(
+                StatusCode::BAD_REQUEST,
+                S3ErrorCode::AuthorizationQueryParametersError,
+                "X-Amz-Date and X-Amz-Expires must be set for a presigned url",
+            ));
+        };
+        if signed_at.and_utc() + chrono::Duration::seconds(expires_in) < Utc::now() {
+            return Err(InMemoryError::new(
+                StatusCode::FORBIDDEN,
+                S3ErrorCode::AccessDenied,
+                "Request has expired",
+            ));
+        }
+        self.query.retain(|key, _| !key.starts_with("X-Amz-"));
+        Ok(())
+    }
+    /// Region from the credential scope of the `Authorization` header
+    fn signing_region(&self) -> Option<&str> {
+        let authorization = self.header(AUTHORIZATION)?;
+        let credential = authorization.split("Credential=").nth(1)?;
+        credential.split('/').nth(2)
+    }
+    fn has_query(&self, name: &str) -> bool {
+        self.query.contains_key(name)
+    }
+    fn query(&self, name: &str) -> Option<&str> {
+        self.query.get(name).map(String::as_str)
+    }
+    fn header(&self, name: impl http::header::AsHeaderName) -> Option<&str> {
+        self.headers.get(name).and_then(|value| value.to_str().ok())
+    }
+}
+#[derive(Debug)]
+struct InMemoryError {
+    status: StatusCode,
+    code: S3ErrorCode,
+    message: String,
+    bucket_region: Option<String>,
+}
+impl InMemoryError {
+    fn new(status: StatusCode, code: S3ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+            bucket_region: None,
+        }
+    }
+    fn no_such_bucket() -> Self {
+        Self::new(
+            StatusCode::NOT_FOUND,
+            S3ErrorCode::NoSuchBucket,
+            "The specified bucket does not exist",
+        )
+    }
+    fn no_such_key() -> Self {
+        Self::new(
+            StatusCode::NOT_FOUND,
+            S3ErrorCode::NoSuchKey,
+            "The specified key does not exist.",
+        )
+    }
+    fn no_such_upload() -> Self {
+        Self::new(
+            StatusCode::NOT_FOUND,
+            S3ErrorCode::NoSuchUpload,
+            "The specified multipart upload does not exist.",
+        )
+    }
+    fn malformed_xml() -> Self {
+        Self::new(
+            StatusCode::BAD_REQUEST,
+            S3ErrorCode::MalformedXML,
+            "The XML you provided was not well-formed or did not validate against our published schema.",
+        )
+    }
+    fn not_implemented() -> Self {
+        Self::new(
+            StatusCode::NOT_IMPLEMENTED,
+            S3ErrorCode::NotImplemented,
+            "A header or query you provided implies functionality that is not implemented.",
+        )
+    }
+    fn into_response(self, request_id: &str) -> S3Response {
+        let body = ErrorBody {
+            code: self.code.to_string(),
+            message: Some(self.message),
+            request_id: Some(request_id.to_owned()),
+            host_id: None,
+            attributes: Default::default(),
+        };
+        let xml = quick_xml::se::to_string_with_root("Error", &body).unwrap_or_default();
+        let mut headers = HeaderMap::new();
+        if let Some(region) = self
+            .bucket_region
+            .and_then(|region| HeaderValue::from_str(&region).ok())
+        {
+            headers.insert(X_AMZ_BUCKET_REGION, region);
+        }
+        response(self.status, headers, Bytes::from(xml))
+    }
+}
+type InMemoryResult = Result<S3Response, InMemoryError>;
+
+impl Default for InMemoryS3 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl InMemoryS3 {
+    pub fn new() -> Self {
+        Self {
+            state: Default::default(),
+            request_counter: Arc::new(AtomicUsize::new(0)),
+            client: reqwest::Client::new(),
+        }
+    }
+    /// Creates a bucket and returns self
+    pub fn with_bucket(self, bucket: impl Into<String>) -> Self {
+        self.create_bucket(bucket);
+        self
+    }
+    /// Responds to ListObjectsV2 with NotImplemented like older S3 implementations.
+    pub fn without_list_objects_v2(self) -> Self {
+        self.state().list_objects_v2 = false;
+        self
+    }
+    /// Creates a bucket if it does not exist.
+    pub fn create_bucket(&self, bucket: impl Into<String>) {
+        self.state().buckets.entry(bucket.into()).or_default();
+    }
+    /// Places the bucket in a region.
+    ///
+    /// Requests signed for a different region are answered with a `PermanentRedirect` like S3 does.
+    pub fn set_bucket_region(&self, bucket: &str, region: impl Into<String>) {
+        if let Some(bucket) = self.state().buckets.get_mut(bucket) {
+            bucket.region = Some(region.into());
+        }
+    }
+    pub fn bucket_exists(&self, bucket: &str) -> bool {
+        self.state().buckets.contains_key(bucket)
+    }
+    /// Stores an object without going through a request
+    ///
+    /// # Panics
+    /// If the bucket does not exist
+    pub fn put_object(&self, bucket: &str, key: impl Into<String>, content: impl Into<Bytes>) {
+        let mut state = self.state();
+        let bucket = state
+            .buckets
+            .get_mut(bucket)
+            .unwrap_or_else(|| panic!("Bucket {bucket} does not exist"));
+        let content = content.into();
+        bucket.objects.insert(
+            key.into(),
+            InMemoryObject {
+                etag: etag(&content),
+                content,
+                content_type: "binary/octet-stream".to_owned(),
+                last_modified: Utc::now(),
+                metadata: HeaderMap::new(),
+                tags: Vec::new(),
+                checksum: None,
+            },
+        );
+    }
+    /// Replaces the checksum that is returned for an object. Panics if the object does not exist.
+    pub fn set_object_checksum(
+        &self,
+        bucket: &str,
+        key: &str,
+        algorithm: ChecksumAlgorithm,
+        checksum: impl Into<String>,
+    ) {
+        let mut state = self.state();
+        let object = state
+            .buckets
+            .get_mut(bucket)
+            .and_then(|bucket| bucket.objects.get_mut(key))
+            .unwrap_or_else(|| panic!("Object {bucket}/{key} does not exist"));
+        object.checksum = Some((algorithm, checksum.into()));
+    }
+    pub fn get_object(&self, bucket: &str, key: &str) -> Option<InMemoryObject> {
+        self.state()
+            .buckets
+            .get(bucket)
+            .and_then(|bucket| bucket.objects.get(key))
+            .cloned()
+    }
+    /// Returns all keys in the bucket in lexicographical order
+    pub fn object_keys(&self, bucket: &str) -> Vec<String> {
+        self.state()
+            .buckets
+            .get(bucket)
+            .map(|bucket| bucket.objects.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+    /// Number of multipart uploads that have not been completed or aborted
+    pub fn pending_uploads(&self, bucket: &str) -> usize {
+        self.state()
+            .buckets
+            .get(bucket)
+            .map(|bucket| bucket.uploads.len())
+            .unwrap_or_default()
+    }
+    pub fn request_count(&self) -> usize {
+        self.request_counter.load(Ordering::SeqCst)
+    }
+    fn state(&self) -> MutexGuard<'_, InMemoryState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+    /// Splits the url into bucket and key.
+    ///
+    /// A host starting with `{bucket}.` for an existing bucket is treated as virtual-hosted-style.
+    fn bucket_and_key(&self, url: &Url) -> (Option<String>, Option<String>) {
+        let path = percent_encoding::percent_decode_str(url.path()).decode_utf8_lossy();
+        let path = path.strip_prefix('/').unwrap_or(&path);
+        let host = url.host_str().unwrap_or_default();
+        let virtual_bucket = self
+            .state()
+            .buckets
+            .keys()
+            .filter(|bucket| {
+                host.strip_prefix(bucket.as_str())
+                    .is_some_and(|rest| rest.starts_with('.'))
+            })
+            .max_by_key(|bucket| bucket.len())
+            .cloned();
+        let (bucket, key) = match virtual_bucket {
+            Some(bucket) => (Some(bucket), path),
+            None => match path.split_once('/') {
+                Some((bucket, key)) => (Some(bucket.to_owned()), key),
+                None => ((!path.is_empty()).then(|| path.to_owned()), ""),
+            },
+        };
+        let key = (!key.is_empty()).then(|| key.to_owned());
+        (bucket, key)
+    }
+    async fn decode_request(&self, mut request: Request) -> Result<InMemoryRequest, InMemoryError> {
+        let (bucket, key) = self.bucket_and_key(request.url());
+        let query = request
+            .url()
+            .query_pairs()
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect();
+        let raw_body = read_body(request.body_mut().take()).await?;
+        let mut headers = std::mem::take(request.headers_mut());
+        let is_chunked = headers
+            .get(CONTENT_ENCODING)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.contains("aws-chunked"))
+            || headers
+                .get(X_AMZ_CONTENT_SHA256)
+                .is_some_and(|value| value.as_bytes().starts_with(b"STREAMING-"));
+        let body = if is_chunked {
+            let (decoded, trailers) = decode_aws_chunked(&raw_body).ok_or_else(|| {
+                InMemoryError::new(
+                    StatusCode::BAD_REQUEST,
+                    S3ErrorCode::IncompleteBody,
+                    "Invalid aws-chunked body",
+                )
+            })?;
+            let expected_length = headers
+                .get(X_AMZ_DECODED_CONTENT_LENGTH)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<usize>().ok());
+            if expected_length.is_some_and(|length| length != decoded.len()) {
+                return Err(InMemoryError::new(
+                    StatusCode::BAD_REQUEST,
+                    S3ErrorCode::IncompleteBody,
+                    "You did not provide the number of bytes specified by the Content-Length HTTP header",
+                ));
+            }
+            headers.extend(trailers);
+            decoded
+        } else {
+            raw_body
+        };
+        let mut request = InMemoryRequest {
+            method: request.method().clone(),
+            bucket,
+            key,
+            query,
+            headers,
+            body,
+        };
+        request.check_request_time()?;
+        request.check_presigned_url()?;
+        request.check_content_md5()?;
+        Ok(request)
+    }
+    async fn handle(&self, request: Request) -> S3Response {
+        let request_id = format!(
+            "{:016X}",
+            self.request_counter.fetch_add(1, Ordering::SeqCst) + 1
+        );
+        let is_head = request.method() == Method::HEAD;
+        let result = match self.decode_request(request).await {
+            Ok(request) => self.state().dispatch(request),
+            Err(err) => Err(err),
+        };
+        let result = result.map_err(|err| {
+            let mut response = err.into_response(&request_id);
+            // HEAD responses never have a body
+            if is_head {
+                *response.body_mut() = Bytes::new();
+            }
+            response
+        });
+        let mut response = result.unwrap_or_else(|response| response);
+        if let Ok(request_id) = HeaderValue::from_str(&request_id) {
+            response.headers_mut().insert(X_AMZ_REQUEST_ID, request_id);
+        }
+        let date = Utc::now().format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+        if let Ok(date) = HeaderValue::from_str(&date) {
+            response.headers_mut().insert(DATE, date);
+        }
+        response
+    }
+}
+impl HttpClient for InMemoryS3 {
+    fn request(&self, method: reqwest::Method, url: Url) -> RequestBuilder {
+        self.client.request(method, url)
+    }
+
+    async fn execute(&self, request: Request) -> Result<Response, Error> {
+        Ok(Response::from(self.handle(request).await))
+    }
+}
+impl InMemoryState {
+    fn dispatch(&mut self, request: InMemoryRequest) -> InMemoryResult {
+        let Some(bucket_name) = request.bucket.clone() else {
+            return match request.method {
+                Method::GET => self.list_buckets(&request),
+                _ => Err(InMemoryError::not_implemented()),
+            };
+        };
+        if let Some(bucket) = self.buckets.get(&bucket_name)
+            && let Some(region) = &bucket.region
+            && request.signing_region() != Some(region.as_str())
+        {
+            let mut error = InMemoryError::new(
+                StatusCode::MOVED_PERMANENTLY,
+                S3ErrorCode::PermanentRedirect,
+                "The bucket you are attempting to access must be addressed using the specified endpoint.",
+            );
+            error.bucket_region = Some(region.clone());
+            return Err(error);
+        }
+        let Some(key) = request.key.clone() else {
+            return self.bucket_operation(&bucket_name, &request);
+        };
+        match request.method {
+            Method::POST if request.has_query("uploads") => {
+                self.create_multipart_upload(&bucket_name, key, &request)
+            }
+            Method::PUT if request.has_query("uploadId") => {
+                self.bucket_mut(&bucket_name)?.upload_part(&request)
+            }
+            Method::POST if request.has_query("uploadId") => self
+                .bucket_mut(&bucket_name)?
+                .complete_multipart_upload(&bucket_name, &key, &request),
+            Method::DELETE if request.has_query("uploadId") => self
+                .bucket_mut(&bucket_name)?
+                .abort_multipart_upload(&request),
+            Method::GET if request.has_query("uploadId") => self
+                .bucket_mut(&bucket_name)?
+                .list_parts(&bucket_name, &request),
+            Method::PUT if request.headers.contains_key(X_AMZ_COPY_SOURCE) => {
+                self.copy_object(&bucket_name, key, &request)
+            }
+            _ => self
+                .bucket_mut(&bucket_name)?
+                .object_operation(key, &request),
+        }
+    }
+    fn bucket_mut(&mut self, bucket: &str) -> Result<&mut InMemoryBucket, InMemoryError> {
+        self.buckets
+            .get_mut(bucket)
+            .ok_or_else(InMemoryError::no_such_bucket)
+    }
+    fn list_buckets(&self, request: &InMemoryRequest) -> InMemoryResult {
+        let prefix = request.query("prefix").unwrap_or_default();
+        let max_buckets = parse_query_number(request, "max-buckets")?.unwrap_or(10000);
+        let start_after = request.query("continuation-token").unwrap_or_default();
+        let bucket_region = request.query("bucket-region");
+        // Buckets without a region are in every region
+        let mut matching = self.buckets.iter().filter(|(name, bucket)| {
+            name.starts_with(prefix)
+                && name.as_str() > start_after
+                && bucket_region
+                    .zip(bucket.region.as_deref())
+                    .is_none_or(|(requested, region)| requested == region)
+        });
+        let buckets: Vec<Bucket> = matching
+            .by_ref()
+            .take(max_buckets)
+            .map(|(name, bucket)| Bucket {
+                creation_date: bucket.creation_date.fixed_offset(),
+                name: name.clone(),
+                bucket_region: bucket.region.clone(),
+            })
+            .collect();
+        let continuation_token = matching
+            .next()
+            .and(buckets.last().map(|bucket| bucket.name.clone()));
+        let result = ListAllMyBuckets {
+            continuation_token,
+            buckets: buckets.into(),
+            prefix: request.query("prefix").map(str::to_owned),
+        };
+        xml_response(StatusCode::OK, "ListAllMyBucketsResult", &result)
+    }
+    fn bucket_operation(&mut self, bucket_name: &str, request: &InMemoryRequest) -> InMemoryResult {
+        match request.method {
+            Method::PUT => {
+                if self.buckets.contains_key(bucket_name) {
+                    return Err(InMemoryError::new(
+                        StatusCode::CONFLICT,
+                        S3ErrorCode::BucketAlreadyOwnedByYou,
+                        "Your previous request to create the named bucket succeeded and you already own it.",
+                    ));
+                }
+                self.buckets
+                    .insert(bucket_name.to_owned(), Default::default());
+                Ok(empty_response(StatusCode::OK))
+            }
+            Method::DELETE => {
+                let bucket = self.bucket_mut(bucket_name)?;
+                if !bucket.objects.is_empty() {
+                    return Err(InMemoryError::new(
+                        StatusCode::CONFLICT,
+                        S3ErrorCode::BucketNotEmpty,
+                        "The bucket you tried to delete is not empty",
+                    ));
+                }
+                self.buckets.remove(bucket_name);
+                Ok(empty_response(StatusCode::NO_CONTENT))
+            }
+            Method::HEAD => {
+                self.bucket_mut(bucket_name)?;
+                Ok(empty_response(StatusCode::OK))
+            }
+            Method::GET if request.query("list-type") == Some("2") => {
+                if !self.list_objects_v2 {
+                    return Err(InMemoryError::not_implemented());
+                }
+                self.bucket_mut(bucket_name)?
+                    .list_objects_v2(bucket_name, request)
+            }
+            Method::GET if is_list_v1_query(request) => self
+                .bucket_mut(bucket_name)?
+                .list_objects_v1(bucket_name, request),
+            Method::POST if request.has_query("delete") => {
+                self.bucket_mut(bucket_name)?.delete_objects(request)
+            }
+            _ => Err(InMemoryError::not_implemented()),
+        }
+    }
+    fn create_multipart_upload(
+        &mut self,
+        bucket_name: &str,
+        key: String,
+        request: &InMemoryRequest,
+    ) -> InMemoryResult {
+        self.next_upload_id += 1;
+        let upload_id = format!("upload-{}", self.next_upload_id);
+        let bucket = self.bucket_mut(bucket_name)?;
+        bucket.uploads.insert(
+            upload_id.clone(),
+            InMemoryUpload {
+                key: key.clone(),
+                content_type: content_type(request),
+                metadata: metadata(&request.headers),
+                tags: tags_from_header(request),
+                checksum_algorithm: request
+                    .header(X_AMZ_CHECKSUM_ALGORITHM)
+                    .and_then(|value| value.parse().ok()),
+                parts: BTreeMap::new(),
+            },
+        );
+        let result = InitiateMultipartUploadResult {
+            bucket: Some(bucket_name.to_owned()),
+            key,
+            upload_id,
+        };
+        xml_response(StatusCode::OK, "InitiateMultipartUploadResult", &result)
+    }
+    fn copy_object(
+        &mut self,
+        bucket_name: &str,
+        key: String,
+        request: &InMemoryRequest,
+    ) -> InMemoryResult {
+        let source = request.header(X_AMZ_COPY_SOURCE).unwrap_or_default();
+        let source = percent_encoding::percent_decode_str(source).decode_utf8_lossy();
+        let source = source.split('?').next().unwrap_or_default();
+        let (source_bucket, source_key) = source
+            .trim_start_matches('/')
+            .split_once('/')
+            .ok_or_else(|| {
+                InMemoryError::new(
+                    StatusCode::BAD_REQUEST,
+                    S3ErrorCode::InvalidArgument,
+                    "Copy Source must mention the source bucket and key: sourcebucket/sourcekey",
+                )
+            })?;
+        let mut object = self
+            .buckets
+            .get(source_bucket)
+            .ok_or_else(InMemoryError::no_such_bucket)?
+            .objects
+            .get(source_key)
+            .cloned()
+            .ok_or_else(InMemoryError::no_such_key)?;
+        object.last_modified = Utc::now();
+        let body = format!(
+            "<CopyObjectResult><LastModified>{}</LastModified><ETag>{}</ETag></CopyObjectResult>",
+            object.last_modified.to_rfc3339(),
+            quick_xml::escape::escape(&object.etag)
+        );
+        self.bucket_mut(bucket_name)?.objects.insert(key, object);
+        Ok(response(
+            StatusCode::OK,
+            HeaderMap::new(),
+            Bytes::from(body),
+        ))
+    }
+}
+impl InMemoryBucket {
+    /// DeleteObjects requires a `Content-MD5` or a `x-amz-checksum-*` header
+    fn delete_objects(&mut self, request: &InMemoryRequest) -> InMemoryResult {
+        let has_digest = request.headers.contains_key(CONTENT_MD5)
+            || ChecksumAlgorithm::ALL
+                .into_iter()
+                .any(|algorithm| request.headers.contains_key(algorithm.header_name()));
+        if !has_digest {
+            return Err(InMemoryError::new(
+                StatusCode::BAD_REQUEST,
+                S3ErrorCode::InvalidRequest,
+                "Missing required header for this request: Content-MD5",
+            ));
+        }
+        let delete: Delete = parse_xml(&request.body)?;
+        let mut result = DeleteResult::default();
+        for object in delete.objects {
+            self.objects.remove(&object.key);
+            if !delete.quiet {
+                result.deleted.push(DeletedObject {
+                    key: object.key,
+                    version_id: object.version_id,
+                    delete_marker: None,
+                    delete_marker_version_id: None,
+                });
+            }
+        }
+        xml_response(StatusCode::OK, "DeleteResult", &result)
+    }
+    fn object_operation(&mut self, key: String, request: &InMemoryRequest) -> InMemoryResult {
+        let is_tagging = request.has_query("tagging");
+        match request.method {
+            Method::GET if is_tagging => {
+                let object = self
+                    .objects
+                    .get(&key)
+                    .ok_or_else(InMemoryError::no_such_key)?;
+                let tagging = OwnedTaggingSet::new(object.tags.clone());
+                xml_response(StatusCode::OK, "Tagging", &tagging)
+            }
+            Method::PUT if is_tagging => {
+                let object = self
+                    .objects
+                    .get_mut(&key)
+                    .ok_or_else(InMemoryError::no_such_key)?;
+                let tagging: OwnedTaggingSet = parse_xml(&request.body)?;
+                object.tags = tagging.into();
+                Ok(empty_response(StatusCode::OK))
+            }
+            Method::DELETE if is_tagging => {
+                let object = self
+                    .objects
+                    .get_mut(&key)
+                    .ok_or_else(InMemoryError::no_such_key)?;
+                object.tags.clear();
+                Ok(empty_response(StatusCode::NO_CONTENT))
+            }
+            Method::PUT if request.has_query("rename") => self.rename_object(key, request),
+            Method::PUT => self.put_object(key, request),
+            Method::GET => self.get_object(&key, request),
+            Method::HEAD => {
+                let object = self
+                    .objects
+                    .get(&key)
+                    .ok_or_else(InMemoryError::no_such_key)?;
+                let mut response = object_response(object, StatusCode::OK, Bytes::new());
+                response
+                    .headers_mut()
+                    .insert(CONTENT_LENGTH, HeaderValue::from(object.content.len()));
+                insert_checksum(&mut response, object, request);
+                Ok(response)
+            }
+            Method::DELETE => {
+                self.objects.remove(&key);
+                Ok(empty_response(StatusCode::NO_CONTENT))
+            }
+            _ => Err(InMemoryError::not_implemented()),
+        }
+    }
+    fn put_object(&mut self, key: String, request: &InMemoryRequest) -> InMemoryResult {
+        if request.header(IF_NONE_MATCH) == Some("*") && self.objects.contains_key(&key) {
+            return Err(InMemoryError::new(
+                StatusCode::PRECONDITION_FAILED,
+                S3ErrorCode::PreconditionFailed,
+                "At least one of the pre-conditions you specified did not hold",
+            ));
+        }
+        let object = InMemoryObject {
+            etag: etag(&request.body),
+            content: request.body.clone(),
+            content_type: content_type(request),
+            last_modified: Utc::now(),
+            metadata: metadata(&request.headers),
+            tags: tags_from_header(request),
+            checksum: request.checksum()?,
+        };
+        let response = etag_response(&object.etag, object.checksum.as_ref());
+        self.objects.insert(key, object);
+        Ok(response)
+    }
+    fn rename_object(&mut self, key: String, request: &InMemoryRequest) -> InMemoryResult {
+        let source = request.header(X_AMZ_RENAME_SOURCE).unwrap_or_default();
+        let source = percent_encoding::percent_decode_str(source).decode_utf8_lossy();
+        // Either `/bucket/key` or just the key
+        let source_key = match source.strip_prefix('/') {
+            Some(path) => path.split_once('/').map(|(_, key)| key).unwrap_or(path),
+            None => &source,
+        };
+        let object = self
+            .objects
+            .remove(source_key)
+            .ok_or_else(InMemoryError::no_such_key)?;
+        self.objects.insert(key, object);
+        Ok(empty_response(StatusCode::OK))
+    }
+    fn get_object(&self, key: &str, request: &InMemoryRequest) -> InMemoryResult {
+        let object = self
+            .objects
+            .get(key)
+            .ok_or_else(InMemoryError::no_such_key)?;
+        let Some(range) = request.header(RANGE) else {
+            let mut response = object_response(object, StatusCode::OK, object.content.clone());
+            insert_checksum(&mut response, object, request);
+            return Ok(response);
+        };
+        let total = object.content.len();
+        let (start, end) = parse_range(range, total).ok_or_else(|| {
+            InMemoryError::new(
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                S3ErrorCode::InvalidRange,
+                "The requested range is not satisfiable",
+            )
+        })?;
+        let mut response = object_response(
+            object,
+            StatusCode::PARTIAL_CONTENT,
+            object.content.slice(start..=end),
+        );
+        if let Ok(content_range) = HeaderValue::from_str(&format!("bytes {start}-{end}/{total}")) {
+            response.headers_mut().insert(CONTENT_RANGE, content_range);
+        }
+        Ok(response)
+    }
+    fn list_objects_v2(&self, bucket_name: &str, request: &InMemoryRequest) -> InMemoryResult {
+        let prefix = request.query("prefix").unwrap_or_default();
+        let delimiter = request.query("delimiter").filter(|value| !value.is_empty());
+        let max_keys = parse_query_number(request, "max-keys")?.unwrap_or(1000);
+        let start_after = request.query("start-after");
+        let marker = request.query("continuation-token").or(start_after);
+        let encoding_type = parse_encoding_type(request)?;
+
+        let (contents, common_prefixes, next_continuation_token) =
+            self.list_entries(prefix, delimiter, max_keys, marker);
+        let mut result = v2::ListBucketResult {
+            is_truncated: next_continuation_token.is_some(),
+            max_keys: Some(max_keys as u32),
+            key_count: Some((contents.len() + common_prefixes.len()) as u32),
+            name: bucket_name.to_owned(),
+            next_continuation_token,
+            prefix: Some(prefix.to_owned()),
+            delimiter: delimiter.map(str::to_owned),
+            start_after: start_after.map(str::to_owned),
+            encoding_type,
+            contents: (!contents.is_empty()).then_some(contents),
+            common_prefixes: (!common_prefixes.is_empty()).then_some(CommonPrefixes {
+                prefix: common_prefixes,
+            }),
+        };
+        if encoding_type.is_some() {
+            let objects = result
+                .contents
+                .iter_mut()
+                .flatten()
+                .map(|object| &mut object.key);
+            let prefixes = result
+                .common_prefixes
+                .iter_mut()
+                .flat_map(|cp| cp.prefix.iter_mut());
+            let fields = [
+                &mut result.prefix,
+                &mut result.delimiter,
+                &mut result.start_after,
+            ]
+            .into_iter()
+            .flatten();
+            objects.chain(prefixes).chain(fields).for_each(url_encode);
+        }
+        xml_response(StatusCode::OK, "ListBucketResult", &result)
+    }
+    fn list_objects_v1(&self, bucket_name: &str, request: &InMemoryRequest) -> InMemoryResult {
+        let prefix = request.query("prefix").unwrap_or_default();
+        let delimiter = request.query("delimiter").filter(|value| !value.is_empty());
+        let max_keys = parse_query_number(request, "max-keys")?.unwrap_or(1000);
+        let marker = request.query("marker").filter(|value| !value.is_empty());
+        let encoding_type = parse_encoding_type(request)?;
+
+        let (contents, common_prefixes, next_marker) =
+            self.list_entries(prefix, delimiter, max_keys, marker);
+        let mut result = v1::ListBucketResult {
+            is_truncated: next_marker.is_some(),
+            max_keys: Some(max_keys as i32),
+            name: bucket_name.to_owned(),
+            marker: Some(marker.unwrap_or_default().to_owned()),
+            // Like S3, NextMarker is only returned for requests with a delimiter
+            next_marker: next_marker.filter(|_| delimiter.is_some()),
+            prefix: Some(prefix.to_owned()),
+            delimiter: delimiter.map(str::to_owned),
+            start_after: None,
+            encoding_type,
+            contents,
+            common_prefixes: (!common_prefixes.is_empty()).then_some(CommonPrefixes {
+                prefix: common_prefixes,
+            }),
+        };
+        if encoding_type.is_some() {
+            let objects = result.contents.iter_mut().map(|object| &mut object.key);
+            let prefixes = result
+                .common_prefixes
+                .iter_mut()
+                .flat_map(|cp| cp.prefix.iter_mut());
+            let fields = [
+                &mut result.prefix,
+                &mut result.delimiter,
+                &mut result.marker,
+                &mut result.next_marker,
+            ]
+            .into_iter()
+            .flatten();
+            objects.chain(prefixes).chain(fields).for_each(url_encode);
+        }
+        xml_response(StatusCode::OK, "ListBucketResult", &result)
+    }
+    /// Lists up to `max_keys` objects and common prefixes after `marker`.
+    ///
+    /// Returns the key of the last entry if the listing was truncated.
+    fn list_entries(
+        &self,
+        prefix: &str,
+        delimiter: Option<&str>,
+        max_keys: usize,
+        marker: Option<&str>,
+    ) -> (Vec<Object>, Vec<String>, Option<String>) {
+        let mut contents = Vec::new();
+        let mut common_prefixes: Vec<String> = Vec::new();
+        let mut next_marker = None;
+        let mut last_entry: Option<String> = None;
+        for (key, object) in &self.objects {
+            if !key.starts_with(prefix) {
+                continue;
+            }
+            if let Some(marker) = marker {
+                let inside_marker_prefix = delimiter.is_some_and(|delimiter| {
+                    marker.ends_with(delimiter) && key.starts_with(marker)
+                });
+                if key.as_str() <= marker || inside_marker_prefix {
+                    continue;
+                }
+            }
+            let common_prefix = delimiter.and_then(|delimiter| {
+                key[prefix.len()..]
+                    .find(delimiter)
+                    .map(|index| key[..prefix.len() + index + delimiter.len()].to_owned())
+            });
+            if let Some(common_prefix) = &common_prefix
+                && common_prefixes.last() == Some(common_prefix)
+            {
+                continue;
+            }
+            if contents.len() + common_prefixes.len() >= max_keys {
+                next_marker = last_entry.take();
+                break;
+            }
+            match common_prefix {
+                Some(common_prefix) => {
+                    last_entry = Some(common_prefix.clone());
+                    common_prefixes.push(common_prefix);
+                }
+                None => {
+                    last_entry = Some(key.clone());
+                    contents.push(Object {
+                        key: key.clone(),
+                        last_modified: object.last_modified.fixed_offset(),
+                        size: object.content.len() as u64,
+                        e_tag: Some(object.etag.clone()),
+                        storage_class: Some(StorageClass::Standard),
+                        owner: None,
+                    });
+                }
+            }
+        }
+        (contents, common_prefixes, next_marker)
+    }
+    fn upload_part(&mut self, request: &InMemoryRequest) -> InMemoryResult {
+        let checksum = request.checksum()?;
+        let upload = self.upload_mut(request)?;
+        let part_number: u32 = request
+            .query("partNumber")
+            .and_then(|value| value.parse().ok())
+            .filter(|number| (1..=10000).contains(number))
+            .ok_or_else(|| {
+                InMemoryError::new(
+                    StatusCode::BAD_REQUEST,
+                    S3ErrorCode::InvalidArgument,
+                    "Part number must be an integer between 1 and 10000, inclusive",
+                )
+            })?;
+        let part_etag = etag(&request.body);
+        // Like S3, the checksum of the upload is calculated if the part was sent without it
+        let checksum = checksum.or_else(|| {
+            upload
+                .checksum_algorithm
+                .map(|algorithm| (algorithm, checksum::checksum(algorithm, &request.body)))
+        });
+        let response = etag_response(&part_etag, checksum.as_ref());
+        upload.parts.insert(
+            part_number,
+            InMemoryPart {
+                etag: part_etag,
+                content: request.body.clone(),
+                last_modified: Utc::now(),
+                checksum,
+            },
+        );
+        Ok(response)
+    }
+    fn complete_multipart_upload(
+        &mut self,
+        bucket_name: &str,
+        key: &str,
+        request: &InMemoryRequest,
+    ) -> InMemoryResult {
+        let complete: CompleteMultipartUpload = parse_xml(&request.body)?;
+        let upload = self.upload_mut(request)?;
+        let mut content = BytesMut::new();
+        let mut digests = Vec::with_capacity(complete.parts.len() * 16);
+        let mut checksums = Vec::new();
+        let mut previous_part = 0;
+        for part in &complete.parts {
+            if part.number <= previous_part {
+                return Err(InMemoryError::new(
+                    StatusCode::BAD_REQUEST,
+                    S3ErrorCode::InvalidPartOrder,
+                    "The list of parts was not in ascending order. Parts must be ordered by part number.",
+                ));
+            }
+            previous_part = part.number;
+            let uploaded = upload
+                .parts
+                .get(&part.number)
+                .filter(|uploaded| uploaded.etag.trim_matches('"') == part.etag.trim_matches('"'))
+                .ok_or_else(|| {
+                    InMemoryError::new(
+                        StatusCode::BAD_REQUEST,
+                        S3ErrorCode::InvalidPart,
+                        "One or more of the specified parts could not be found.",
+                    )
+                })?;
+            if let Some(algorithm) = upload.checksum_algorithm {
+                let Some(reported) = part.checksum(algorithm) else {
+                    return Err(InMemoryError::new(
+                        StatusCode::BAD_REQUEST,
+                        S3ErrorCode::InvalidRequest,
+                        format!(
+                            "The upload was created using a {algorithm} checksum. The complete request must include the checksum for each part."
+                        ),
+                    ));
+                };
+                let uploaded_checksum = uploaded
+                    .checksum
+                    .as_ref()
+                    .filter(|(uploaded_algorithm, _)| *uploaded_algorithm == algorithm)
+                    .map(|(_, checksum)| checksum.as_str());
+                if uploaded_checksum != Some(reported) {
+                    return Err(InMemoryError::new(
+                        StatusCode::BAD_REQUEST,
+                        S3ErrorCode::InvalidPart,
+                        "One or more of the specified parts could not be found.",
+                    ));
+                }
+                checksums.extend(STANDARD.decode(reported).unwrap_or_default());
+            }
+            content.extend_from_slice(&uploaded.content);
+            digests.extend(hex::decode(uploaded.etag.trim_matches('"')).unwrap_or_default());
+        }
+        let upload_id = request.query("uploadId").unwrap_or_default();
+        let Some(upload) = self.uploads.remove(upload_id) else {
+            return Err(InMemoryError::no_such_upload());
+        };
+        let object_etag = format!("\"{:x}-{}\"", md5::compute(&digests), complete.parts.len());
+        // The checksum of the checksums of the parts
+        let object_checksum = upload.checksum_algorithm.map(|algorithm| {
+            let checksum = checksum::checksum(algorithm, &checksums);
+            (algorithm, format!("{checksum}-{}", complete.parts.len()))
+        });
+        let body = format!(
+            "<CompleteMultipartUploadResult><Bucket>{bucket}</Bucket><Key>{key}</Key><ETag>{etag}</ETag></CompleteMultipartUploadResult>",
+            bucket = quick_xml::escape::escape(bucket_name),
+            key = quick_xml::escape::escape(key),
+            etag = quick_xml::escape::escape(&object_etag),
+        );
+        self.objects.insert(
+            upload.key,
+            InMemoryObject {
+                content: content.freeze(),
+                content_type: upload.content_type,
+                etag: object_etag,
+                last_modified: Utc::now(),
+                metadata: upload.metadata,
+                tags: upload.tags,
+                checksum: object_checksum,
+            },
+        );
+        Ok(response(
+            StatusCode::OK,
+            HeaderMap::new(),
+            Bytes::from(body),
+        ))
+    }
+    fn list_parts(&mut self, bucket_name: &str, request: &InMemoryRequest) -> InMemoryResult {
+        let max_parts = parse_query_number(request, "max-parts")?.unwrap_or(1000);
+        let marker = parse_query_number(request, "part-number-marker")?.unwrap_or(0);
+        let upload = self.upload_mut(request)?;
+        let mut remaining = upload.parts.range(marker as u32 + 1..);
+        let parts: Vec<UploadedPart> = remaining
+            .by_ref()
+            .take(max_parts)
+            .map(|(number, part)| {
+                let uploaded = UploadedPart {
+                    part_number: *number,
+                    last_modified: part.last_modified.fixed_offset(),
+                    etag: part.etag.clone(),
+                    size: part.content.len() as u64,
+                    ..Default::default()
+                };
+                match &part.checksum {
+                    Some((algorithm, checksum)) => uploaded.with_checksum(*algorithm, checksum),
+                    None => uploaded,
+                }
+            })
+            .collect();
+        let is_truncated = remaining.next().is_some();
+        let result = ListPartsResult {
+            bucket: bucket_name.to_owned(),
+            key: upload.key.clone(),
+            upload_id: request.query("uploadId").unwrap_or_default().to_owned(),
+            part_number_marker: Some(marker as u32),
+            next_part_number_marker: parts.last().map(|part| part.part_number),
+            max_parts: Some(max_parts as u32),
+            is_truncated,
+            parts,
+        };
+        xml_response(StatusCode::OK, "ListPartsResult", &result)
+    }
+    fn abort_multipart_upload(&mut self, request: &InMemoryRequest) -> InMemoryResult {
+        let upload_id = request.query("uploadId").unwrap_or_default();
+        self.uploads
+            .remove(upload_id)
+            .ok_or_else(InMemoryError::no_such_upload)?;
+        Ok(empty_response(StatusCode::NO_CONTENT))
+    }
+    fn upload_mut(
+        &mut self,
+        request: &InMemoryRequest,
+    ) -> Result<&mut InMemoryUpload, InMemoryError> {
+        let upload_id = request.query("uploadId").unwrap_or_default();
+        self.uploads
+            .get_mut(upload_id)
+            .filter(|upload| request.key.as_deref() == Some(upload.key.as_str()))
+            .ok_or_else(InMemoryError::no_such_upload)
+    }
+}
+/// Reads the entire request body. Streaming bodies are polled to the end.
+async fn read_body(body: Option<reqwest::Body>) -> Result<Bytes, InMemoryError> {
+    let Some(mut body) = body else {
+        return Ok(Bytes::new());
+    };
+    if let Some(bytes) = body.as_bytes() {
+        return Ok(Bytes::copy_from_slice(bytes));
+    }
+    let mut content = BytesMut::new();
+    while let Some(frame) = futures::future::poll_fn(|cx| Pin::new(&mut body).poll_frame(cx)).await
+    {
+        let frame = frame.map_err(|err| {
+            InMemoryError::new(
+                StatusCode::BAD_REQUEST,
+                S3ErrorCode::IncompleteBody,
+                err.to_string(),
+            )
+        })?;
+        if let Ok(data) = frame.into_data() {
+            content.extend_from_slice(&data);
+        }
+    }
+    Ok(content.freeze())
+}
+/// Decodes an `aws-chunked` body and returns the trailing headers. Chunk and trailer signatures are ignored.
+fn decode_aws_chunked(mut body: &[u8]) -> Option<(Bytes, HeaderMap)> {
+    let mut decoded = BytesMut::with_capacity(body.len());
+    loop {
+        let line_end = body.windows(2).position(|window| window == b"\r\n")?;
+        let header = std::str::from_utf8(&body[..line_end]).ok()?;
+        let size_hex = header.split(';').next()?.trim();
+        let size = usize::from_str_radix(size_hex, 16).ok()?;
+        body = &body[line_end + 2..];
+        if size == 0 {
+            let trailers = std::str::from_utf8(body)
+                .ok()?
+                .lines()
+                .filter_map(|line| line.split_once(':'))
+                .filter(|(name, _)| *name != "x-amz-trailer-signature")
+                .filter_map(|(name, value)| {
+                    Some((
+                        http::HeaderName::from_bytes(name.trim().as_bytes()).ok()?,
+                        HeaderValue::from_str(value.trim()).ok()?,
+                    ))
+                })
+                .collect();
+            return Some((decoded.freeze(), trailers));
+        }
+        decoded.extend_from_slice(body.get(..size)?);
+        body = body.get(size..)?.strip_prefix(b"\r\n")?;
+    }
+}
+/// Parses a `Range` header into an inclusive range.
+///
+/// `bytes=start` is accepted as `bytes=start-`
+fn parse_range(range: &str, total: usize) -> Option<(usize, usize)> {
+    let range = range.strip_prefix("bytes=")?;
+    let (start, end) = range.split_once('-').unwrap_or((range, ""));
+    let (start, end) = match (start.parse::<usize>().ok(), end.parse::<usize>().ok()) {
+        (Some(start), Some(end)) => (start, end.min(total.checked_sub(1)?)),
+        (Some(start), None) => (start, total.checked_sub(1)?),
+        (None, Some(suffix)) if suffix > 0 => (total.saturating_sub(suffix), total.checked_sub(1)?),
+        _ => return None,
+    };
+    (start <= end).then_some((start, end))
+}
+fn parse_encoding_type(request: &InMemoryRequest) -> Result<Option<EncodingType>, InMemoryError> {
+    match request.query("encoding-type") {
+        None => Ok(None),
+        Some("url") => Ok(Some(EncodingType::Url)),
+        Some(_) => Err(InMemoryError::new(
+            StatusCode::BAD_REQUEST,
+            S3ErrorCode::InvalidArgument,
+            "Invalid Encoding Method specified in Request",
+        )),
+    }
+}
+/// Encodes a value of a listing like S3 does for [EncodingType::Url]
+fn url_encode(value: &mut String) {
+    const LIST_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+        .remove(b'-')
+        .remove(b'_')
+        .remove(b'.')
+        .remove(b'~');
+    *value = utf8_percent_encode(value, LIST_ENCODE_SET)
+        .to_string()
+        .replace("%20", "+");
+}
+fn parse_query_number(
+    request: &InMemoryRequest,
+    name: &str,
+) -> Result<Option<usize>, InMemoryError> {
+    request
+        .query(name)
+        .map(|value| {
+            value.parse().map_err(|_| {
+                InMemoryError::new(
+                    StatusCode::BAD_REQUEST,
+                    S3ErrorCode::InvalidArgument,
+                    format!("Provided {name} not an integer or within integer range"),
+                )
+            })
+        })
+        .transpose()
+}
+fn parse_xml<T: serde::de::DeserializeOwned>(body: &[u8]) -> Result<T, InMemoryError> {
+    let body = std::str::from_utf8(body).map_err(|_| InMemoryError::malformed_xml())?;
+    quick_xml::de::from_str(body).map_err(|_| InMemoryError::malformed_xml())
+}
+fn etag(content: &[u8]) -> String {
+    format!("\"{:x}\"", md5::compute(content))
+}
+fn content_type(request: &InMemoryRequest) -> String {
+    request
+        .header(CONTENT_TYPE)
+        .unwrap_or("binary/octet-stream")
+        .to_owned()
+}
+fn metadata(headers: &HeaderMap) -> HeaderMap {
+    headers
+        .iter()
+        .filter(|(name, _)| {
+            name.as_str()
+                .starts_with(crate::command::put::AMZ_METADATA_PREFIX)
+        })
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect()
+}
+fn tags_from_header(request: &InMemoryRequest) -> Vec<OwnedTag> {
+    let Some(tagging) = request.header(X_AMZ_TAGGING) else {
+        return Vec::new();
+    };
+    url::form_urlencoded::parse(tagging.as_bytes())
+        .map(|(key, value)| OwnedTag {
+            key: key.into_owned(),
+            value: value.into_owned(),
+        })
+        .collect()
+}
+fn response(status: StatusCode, mut headers: HeaderMap, body: Bytes) -> S3Response {
+    headers
+        .entry(CONTENT_LENGTH)
+        .or_insert_with(|| HeaderValue::from(body.len()));
+    let mut response = http::Response::new(body);
+    *response.status_mut() = status;
+    *response.headers_mut() = headers;
+    response
+}
+fn empty_response(status: StatusCode) -> S3Response {
+    response(status, HeaderMap::new(), Bytes::new())
+}
+/// Response of an upload with the ETag and the checksum S3 verified
+fn etag_response(etag: &str, checksum: Option<&(ChecksumAlgorithm, String)>) -> S3Response {
+    let mut headers = HeaderMap::new();
+    if let Ok(etag) = HeaderValue::from_str(etag) {
+        headers.insert(ETAG, etag);
+    }
+    if let Some((algorithm, checksum)) = checksum
+        && let Ok(checksum) = HeaderValue::from_str(checksum)
+    {
+        headers.insert(algorithm.header_name(), checksum);
+    }
+    response(StatusCode::OK, headers, Bytes::new())
+}
+fn object_response(object: &InMemoryObject, status: StatusCode, body: Bytes) -> S3Response {
+    let mut headers = object.metadata.clone();
+    if let Ok(etag) = HeaderValue::from_str(&object.etag) {
+        headers.insert(ETAG, etag);
+    }
+    if let Ok(content_type) = HeaderValue::from_str(&object.content_type) {
+        headers.insert(CONTENT_TYPE, content_type);
+    }
+    let last_modified = object
+        .last_modified
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string();
+    if let Ok(last_modified) = HeaderValue::from_str(&last_modified) {
+        headers.insert(LAST_MODIFIED, last_modified);
+    }
+    response(status, headers, body)
+}
+/// Adds the checksum of the object if the request has `x-amz-checksum-mode: ENABLED`
+fn insert_checksum(response: &mut S3Response, object: &InMemoryObject, request: &InMemoryRequest) {
+    if request.header(X_AMZ_CHECKSUM_MODE) != Some("ENABLED") {
+        return;
+    }
+    let Some((algorithm, checksum)) = &object.checksum else {
+        return;
+    };
+    let Ok(value) = HeaderValue::from_str(checksum) else {
+        return;
+    };
+    let checksum_type = if checksum.contains('-') {
+        "COMPOSITE"
+    } else {
+        "FULL_OBJECT"
+    };
+    let headers = response.headers_mut();
+    headers.insert(algorithm.header_name(), value);
+    headers.insert(X_AMZ_CHECKSUM_TYPE, HeaderValue::from_static(checksum_type));
+}
+/// Only ListObjects (V1) parameters are in the query
+fn is_list_v1_query(request: &InMemoryRequest) -> bool {
+    request.query.keys().all(|key| {
+        matches!(
+            key.as_str(),
+            "prefix" | "delimiter" | "marker" | "max-keys" | "encoding-type"
+        )
+    })
+}
+fn xml_response<T: Serialize>(status: StatusCode, root: &str, content: &T) -> InMemoryResult {
+    let xml = quick_xml::se::to_string_with_root(root, content).map_err(|err| {
+        InMemoryError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            S3ErrorCode::InternalError,
+            err.to_string(),
+        )
+    })?;
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/xml"));
+    Ok(response(status, headers, Bytes::from(xml)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aws_chunked() {
+        let body = b"5;chunk-signature=abc\r\nhello\r\n6;chunk-signature=def\r\n world\r\n0;chunk-signature=ghi\r\n\r\n";
+        let (decoded, trailers) = decode_aws_chunked(body).expect("Valid body");
+        assert_eq!(decoded, b"hello world".as_slice());
+        assert!(trailers.is_empty());
+        assert_eq!(decode_aws_chunked(b"5\r\nhel"), None);
+
+        let body = b"5;chunk-signature=abc\r\nhello\r\n0;chunk-signature=def\r\nx-amz-checksum-crc32:NhCmhg==\r\nx-amz-trailer-signature:ghi\r\n\r\n";
+        let (decoded, trailers) = decode_aws_chunked(body).expect("Valid body");
+        assert_eq!(decoded, b"hello".as_slice());
+        assert_eq!(trailers.len(), 1);
+        assert_eq!(trailers["x-amz-checksum-crc32"], "NhCmhg==");
+    }
+    #[test]
+    fn ranges() {
+        assert_eq!(parse_range("bytes=0-4", 10), Some((0, 4)));
+        assert_eq!(parse_range("bytes=5-", 10), Some((5, 9)));
+        assert_eq!(parse_range("bytes=5", 10), Some((5, 9)));
+        assert_eq!(parse_range("bytes=-3", 10), Some((7, 9)));
+        assert_eq!(parse_range("bytes=8-20", 10), Some((8, 9)));
+        assert_eq!(parse_range("bytes=10-", 10), None);
+    }
+    #[test]
+    fn bucket_and_key() {
+        let s3 = InMemoryS3::new().with_bucket("bucket");
+        let decode = |url: &str| s3.bucket_and_key(&Url::parse(url).unwrap());
+        assert_eq!(
+            decode("http://localhost:9000/bucket/dir/file.txt"),
+            (Some("bucket".into()), Some("dir/file.txt".into()))
+        );
+        assert_eq!(
+            decode("https://bucket.s3.us-east-1.amazonaws.com/dir/file.txt"),
+            (Some("bucket".into()), Some("dir/file.txt".into()))
+        );
+        assert_eq!(
+            decode("http://localhost:9000/other/"),
+            (Some("other".into()), None)
+        );
+        assert_eq!(decode("http://localhost:9000/"), (None, None));
+    }
+    #[tokio::test]
+    async fn virtual_hosted_style() -> anyhow::Result<()> {
+        let s3 = InMemoryS3::new().with_bucket("bucket");
+        let client = crate::client::S3ClientBuilder::default()
+            .with_region(tux_io_s3_types::region::OfficialRegion::UsEast1)
+            .with_credentials(Default::default())
+            .with_transport(s3.clone())
+            .bucket_client("bucket")?;
+        s3.put_object("bucket", "dir/file.txt", "content");
+        let response = client.get_object("dir/file.txt").await?.expect("Object");
+        assert_eq!(response.0.text().await?, "content");
+        Ok(())
+    }
+}
@@ -0,0 +1,209 @@
+use std::fmt::Debug;
+
+use http::{HeaderMap, Method};
+use reqwest::{Request, Response};
+use url::Url;
+
+use crate::S3Error;
+/// Information about the command passed to every [Interceptor] hook.
+#[derive(Debug, Clone)]
+pub struct InterceptorContext {
+    /// [crate::command::CommandType::name] of the command
+    pub command: &'static str,
+    pub method: Method,
+    /// The url after [crate::command::CommandType::update_url]
+    pub url: Url,
+    /// The current attempt. Starts at 1 and increases when the request is retried.
+    pub attempt: u32,
+}
+/// Hooks called by [crate::client::S3Client] and [crate::client::BucketClient] while executing a command.
+///
+/// Interceptors are registered with [crate::client::S3ClientBuilder::with_interceptor].
+/// Request hooks run in registration order and [Interceptor::after_response] runs in reverse order.
+///
+/// Return [S3Error::Interceptor] from a hook to fail the command.
+pub trait Interceptor: Debug + Send + Sync {
+    /// Called once after [crate::command::CommandType::headers].
+    ///
+    /// Headers added here are signed and sent with every attempt.
+    fn after_headers(
+        &self,
+        _context: &InterceptorContext,
+        _headers: &mut HeaderMap,
+    ) -> Result<(), S3Error> {
+        Ok(())
+    }
+    /// Called before every attempt is signed.
+    ///
+    /// Headers added here are signed.
+    fn before_signing(
+        &self,
+        _context: &InterceptorContext,
+        _headers: &mut HeaderMap,
+    ) -> Result<(), S3Error> {
+        Ok(())
+    }
+    /// Called with the signed request of every attempt.
+    ///
+    /// Returning a response skips sending the request. Changes to the request are not signed.
+    fn after_signing(
+        &self,
+        _context: &InterceptorContext,
+        _request: &mut Request,
+    ) -> Result<Option<Response>, S3Error> {
+        Ok(None)
+    }
+    /// Called once with the result of the command after all retries.
+    fn after_response(
+        &self,
+        _context: &InterceptorContext,
+        result: Result<Response, S3Error>,
+    ) -> Result<Response, S3Error> {
+        result
+    }
+}
+impl<T: Interceptor + ?Sized> Interceptor for std::sync::Arc<T> {
+    fn after_headers(
+        &self,
+        context: &InterceptorContext,
+        headers: &mut HeaderMap,
+    ) -> Result<(), S3Error> {
+        (**self).after_headers(context, headers)
+    }
+    fn before_signing(
+        &self,
+        context: &InterceptorContext,
+        headers: &mut HeaderMap,
+    ) -> Result<(), S3Error> {
+        (**self).before_signing(context, headers)
+    }
+    fn after_signing(
+        &self,
+        context: &InterceptorContext,
+        request: &mut Request,
+    ) -> Result<Option<Response>, S3Error> {
+        (**self).after_signing(context, request)
+    }
+    fn after_response(
+        &self,
+        context: &InterceptorContext,
+        result: Result<Response, S3Error>,
+    ) -> Result<Response, S3Error> {
+        (**self).after_response(context, result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use bytes::Bytes;
+    use http::{HeaderName, HeaderValue, StatusCode, header::AUTHORIZATION};
+
+    use super::*;
+    use crate::{
+        client::{BucketClient, http_client::InMemoryS3},
+        test::{IN_MEMORY_BUCKET, in_memory_client_builder, init_test_logger},
+    };
+    const CORRELATION_ID: HeaderName = HeaderName::from_static("x-correlation-id");
+
+    #[derive(Debug, Default)]
+    struct CorrelationId {
+        signed_headers: Mutex<Vec<String>>,
+    }
+    impl Interceptor for CorrelationId {
+        fn after_headers(
+            &self,
+            _context: &InterceptorContext,
+            headers: &mut HeaderMap,
+        ) -> Result<(), S3Error> {
+            headers.insert(CORRELATION_ID, HeaderValue::from_static("1234"));
+            Ok(())
+        }
+        fn after_signing(
+            &self,
+            _context: &InterceptorContext,
+            request: &mut Request,
+        ) -> Result<Option<Response>, S3Error> {
+            let authorization = request.headers()[AUTHORIZATION].to_str().unwrap();
+            self.signed_headers
+                .lock()
+                .unwrap()
+                .push(authorization.to_owned());
+            Ok(None)
+        }
+    }
+    #[derive(Debug)]
+    struct Cached;
+    impl Interceptor for Cached {
+        fn after_signing(
+            &self,
+            context: &InterceptorContext,
+            _request: &mut Request,
+        ) -> Result<Option<Response>, S3Error> {
+            if context.method != Method::GET {
+                return Ok(None);
+            }
+            let response = http::Response::new(Bytes::from_static(b"cached"));
+            Ok(Some(Response::from(response)))
+        }
+    }
+    #[derive(Debug)]
+    struct NotFoundIsEmpty;
+    impl Interceptor for NotFoundIsEmpty {
+        fn after_response(
+            &self,
+            _context: &InterceptorContext,
+            result: Result<Response, S3Error>,
+        ) -> Result<Response, S3Error> {
+            let response = result?;
+            if response.status() == StatusCode::NOT_FOUND {
+                return Ok(Response::from(http::Response::new(Bytes::new())));
+            }
+            Ok(response)
+        }
+    }
+    fn client_with(
+        s3: &InMemoryS3,
+        interceptor: impl Interceptor + 'static,
+    ) -> BucketClient<InMemoryS3> {
+        in_memory_client_builder(s3)
+            .with_interceptor(interceptor)
+            .bucket_client(IN_MEMORY_BUCKET)
+            .unwrap()
+    }
+    #[tokio::test]
+    async fn added_headers_are_signed() -> anyhow::Result<()> {
+        init_test_logger();
+        let s3 = InMemoryS3::new().with_bucket(IN_MEMORY_BUCKET);
+        let interceptor = Arc::new(CorrelationId::default());
+        let client = in_memory_client_builder(&s3)
+            .with_interceptor(interceptor.clone())
+            .bucket_client(IN_MEMORY_BUCKET)?;
+        client.head_object("file.txt").await?;
+        let signed_headers = interceptor.signed_headers.lock().unwrap();
+        assert_eq!(signed_headers.len(), 1);
+        assert!(signed_headers[0].contains("x-correlation-id"));
+        Ok(())
+    }
+    #[tokio::test]
+    async fn short_circuit() -> anyhow::Result<()> {
+        init_test_logger();
+        let s3 = InMemoryS3::new().with_bucket(IN_MEMORY_BUCKET);
+        let client = client_with(&s3, Cached);
+        let response = client.get_object("file.txt").await?.expect("Cached object");
+        assert_eq!(response.0.text().await?, "cached");
+        assert_eq!(s3.request_count(), 0);
+        Ok(())
+    }
+    #[tokio::test]
+    async fn rewrite_response() -> anyhow::Result<()> {
+        init_test_logger();
+        let s3 = InMemoryS3::new().with_bucket(IN_MEMORY_BUCKET);
+        let client = client_with(&s3, NotFoundIsEmpty);
+        let response = client.get_object("file.txt").await?.expect("Empty object");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(s3.request_count(), 1);
+        Ok(())
+    }
+}
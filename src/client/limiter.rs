@@ -0,0 +1,166 @@
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use http::Method;
+use http_body::{Body as HttpBody, Frame, SizeHint};
+use pin_project::pin_project;
+use reqwest::{Body, Response, ResponseBuilderExt};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+/// Limits how many requests of a client are in flight at the same time.
+///
+/// `GET` and `HEAD` requests count against the read budget. All other requests count against the write budget.
+/// A permit is held until the body of the response has been read or dropped.
+///
+/// Set with [crate::client::S3ClientBuilder::with_concurrency_limit]. All [crate::client::BucketClient]s opened from the same client share the limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConcurrencyLimit {
+    /// Maximum number of reads in flight. None is unlimited.
+    pub reads: Option<usize>,
+    /// Maximum number of writes in flight. None is unlimited.
+    pub writes: Option<usize>,
+}
+impl ConcurrencyLimit {
+    /// No limit for reads or writes
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+    pub fn with_reads(mut self, reads: usize) -> Self {
+        self.reads = Some(reads.max(1));
+        self
+    }
+    pub fn with_writes(mut self, writes: usize) -> Self {
+        self.writes = Some(writes.max(1));
+        self
+    }
+}
+/// The semaphores of a [ConcurrencyLimit]
+#[derive(Debug, Default)]
+pub(crate) struct ConcurrencyLimiter {
+    reads: Option<Arc<Semaphore>>,
+    writes: Option<Arc<Semaphore>>,
+}
+impl From<ConcurrencyLimit> for ConcurrencyLimiter {
+    fn from(limit: ConcurrencyLimit) -> Self {
+        Self {
+            reads: limit.reads.map(|permits| Arc::new(Semaphore::new(permits))),
+            writes: limit
+                .writes
+                .map(|permits| Arc::new(Semaphore::new(permits))),
+        }
+    }
+}
+impl ConcurrencyLimiter {
+    /// Waits for a permit of the budget of `method`. Returns None if the budget is unlimited.
+    pub(crate) async fn acquire(&self, method: &Method) -> Option<OwnedSemaphorePermit> {
+        let semaphore = if matches!(*method, Method::GET | Method::HEAD) {
+            self.reads.as_ref()
+        } else {
+            self.writes.as_ref()
+        }?;
+        // The semaphores are never closed
+        semaphore.clone().acquire_owned().await.ok()
+    }
+}
+/// Keeps `permit` until the body of the response has been read or dropped.
+pub(crate) fn hold_permit(response: Response, permit: OwnedSemaphorePermit) -> Response {
+    let url = response.url().clone();
+    let (mut parts, body) = http::Response::<Body>::from(response).into_parts();
+    if let Some(extensions) = http::response::Builder::new().url(url).extensions_mut() {
+        parts.extensions.extend(std::mem::take(extensions));
+    }
+    let body = PermitBody {
+        inner: body,
+        permit: Some(permit),
+    };
+    Response::from(http::Response::from_parts(parts, Body::wrap(body)))
+}
+#[pin_project]
+struct PermitBody<B> {
+    #[pin]
+    inner: B,
+    /// Released once the body has been read
+    permit: Option<OwnedSemaphorePermit>,
+}
+impl<B: HttpBody<Data = Bytes>> HttpBody for PermitBody<B> {
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let result = this.inner.poll_frame(cx);
+        if let Poll::Ready(None) = result {
+            this.permit.take();
+        }
+        result
+    }
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        client::http_client::InMemoryS3,
+        command::delete::DeleteObject,
+        test::{IN_MEMORY_BUCKET, in_memory_client_builder, init_test_logger},
+    };
+
+    #[tokio::test]
+    async fn permit_is_held_until_body_is_read() -> anyhow::Result<()> {
+        init_test_logger();
+        let s3 = InMemoryS3::new().with_bucket(IN_MEMORY_BUCKET);
+        s3.put_object(IN_MEMORY_BUCKET, "test.txt", "content");
+        let client = in_memory_client_builder(&s3)
+            .with_concurrency_limit(ConcurrencyLimit::unlimited().with_reads(1))
+            .build()?
+            .open_bucket(IN_MEMORY_BUCKET);
+        let other_client = client.clone();
+
+        let first = client.get_object("test.txt").await?.expect("Object");
+        let second = tokio::time::timeout(
+            Duration::from_millis(100),
+            other_client.get_object("test.txt"),
+        )
+        .await;
+        assert!(second.is_err(), "The read budget should be used up");
+        assert_eq!(first.0.text().await?, "content");
+
+        let second = other_client.get_object("test.txt").await?.expect("Object");
+        assert_eq!(second.0.text().await?, "content");
+        Ok(())
+    }
+    #[tokio::test]
+    async fn writes_have_a_separate_budget() -> anyhow::Result<()> {
+        init_test_logger();
+        let s3 = InMemoryS3::new().with_bucket(IN_MEMORY_BUCKET);
+        s3.put_object(IN_MEMORY_BUCKET, "test.txt", "content");
+        let client = in_memory_client_builder(&s3)
+            .with_concurrency_limit(ConcurrencyLimit::unlimited().with_reads(1))
+            .bucket_client(IN_MEMORY_BUCKET)?;
+        let _held = client.get_object("test.txt").await?.expect("Object");
+        let deleted = tokio::time::timeout(
+            Duration::from_millis(100),
+            client.execute_command(DeleteObject {
+                key: "test.txt",
+                version_id: None,
+            }),
+        )
+        .await;
+        assert!(deleted.is_ok(), "Writes are unlimited");
+        Ok(())
+    }
+}
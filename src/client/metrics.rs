@@ -0,0 +1,150 @@
+use std::{
+    fmt::Debug,
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+use ahash::AHashMap;
+use http::{Method, StatusCode};
+/// Measurements of a single command passed to [MetricsSink::record].
+#[derive(Debug, Clone)]
+pub struct CommandMetrics {
+    /// [crate::command::CommandType::name] of the command
+    pub command: &'static str,
+    pub method: Method,
+    /// Status of the final response. None if no response was received.
+    pub status: Option<StatusCode>,
+    /// Time from the start of the command until the response headers of the final attempt were received.
+    pub latency: Duration,
+    /// Body bytes sent over all attempts.
+    pub request_bytes: u64,
+    /// Length of the final response body if it is known.
+    pub response_bytes: Option<u64>,
+    /// Number of attempts after the first one.
+    pub retries: u32,
+}
+impl CommandMetrics {
+    /// Returns true if the command failed or S3 responded with an error.
+    pub fn is_error(&self) -> bool {
+        !self.status.is_some_and(|status| status.is_success())
+    }
+}
+/// Receives [CommandMetrics] for every command executed by a client.
+///
+/// Registered with [crate::client::S3ClientBuilder::with_metrics_sink].
+/// [MetricsSink::record] is called on the request path and should not block.
+pub trait MetricsSink: Debug + Send + Sync {
+    fn record(&self, metrics: &CommandMetrics);
+}
+impl<T: MetricsSink + ?Sized> MetricsSink for std::sync::Arc<T> {
+    fn record(&self, metrics: &CommandMetrics) {
+        (**self).record(metrics)
+    }
+}
+/// Totals of all recorded commands with the same name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandTotals {
+    pub count: u64,
+    /// Commands where [CommandMetrics::is_error] is true
+    pub errors: u64,
+    pub retries: u64,
+    pub request_bytes: u64,
+    pub response_bytes: u64,
+    pub latency: Duration,
+}
+/// A [MetricsSink] that sums up the metrics of every command in memory.
+#[derive(Debug, Default)]
+pub struct InMemoryMetrics {
+    totals: Mutex<AHashMap<&'static str, CommandTotals>>,
+}
+impl InMemoryMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Returns the totals of the command with the given [crate::command::CommandType::name]
+    pub fn command(&self, command: &str) -> Option<CommandTotals> {
+        self.totals
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(command)
+            .cloned()
+    }
+    /// Returns the totals of every recorded command
+    pub fn snapshot(&self) -> AHashMap<&'static str, CommandTotals> {
+        self.totals
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+    pub fn reset(&self) {
+        self.totals
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+impl MetricsSink for InMemoryMetrics {
+    fn record(&self, metrics: &CommandMetrics) {
+        let mut totals = self.totals.lock().unwrap_or_else(PoisonError::into_inner);
+        let totals = totals.entry(metrics.command).or_default();
+        totals.count += 1;
+        if metrics.is_error() {
+            totals.errors += 1;
+        }
+        totals.retries += u64::from(metrics.retries);
+        totals.request_bytes += metrics.request_bytes;
+        totals.response_bytes += metrics.response_bytes.unwrap_or_default();
+        totals.latency += metrics.latency;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        client::{http_client::InMemoryS3, retry::RetryPolicy},
+        command::{CommandType, S3CommandBody, get::GetObject, put::PutObject},
+        test::{IN_MEMORY_BUCKET, in_memory_client_builder, init_test_logger},
+    };
+
+    #[tokio::test]
+    async fn records_commands() -> anyhow::Result<()> {
+        init_test_logger();
+        let s3 = InMemoryS3::new().with_bucket(IN_MEMORY_BUCKET);
+        let metrics = Arc::new(InMemoryMetrics::new());
+        let client = in_memory_client_builder(&s3)
+            .with_retry_policy(RetryPolicy::disabled())
+            .with_metrics_sink(metrics.clone())
+            .bucket_client(IN_MEMORY_BUCKET)?;
+        for (key, content) in [("test.txt", "Hello World"), ("other.txt", "Hello")] {
+            let put = PutObject {
+                key,
+                content: S3CommandBody::from(content),
+                ..Default::default()
+            };
+            client.execute_command(put).await?;
+        }
+        let object = client.get_object("test.txt").await?.expect("Object");
+        let _ = object.0.bytes().await?;
+        assert!(client.get_object("missing.txt").await.is_err());
+
+        let put = metrics
+            .command(PutObject::default().name())
+            .expect("PutObject metrics");
+        assert_eq!(put.count, 2);
+        assert_eq!(put.errors, 0);
+        assert_eq!(put.request_bytes, 16);
+
+        let get = metrics
+            .command(GetObject::default().name())
+            .expect("GetObject metrics");
+        assert_eq!(get.count, 2);
+        assert_eq!(get.errors, 1);
+        assert_eq!(get.request_bytes, 0);
+        assert!(get.response_bytes >= 11);
+        assert_eq!(metrics.snapshot().len(), 2);
+        Ok(())
+    }
+}
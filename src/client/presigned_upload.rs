@@ -0,0 +1,372 @@
+use std::{borrow::Cow, time::Duration};
+
+use tux_io_s3_types::{
+    S3ContentError,
+    error::Error as ErrorBody,
+    multi_part::{
+        CompleteMultipartUploadResult, InitiateMultipartUploadResult, ListPartsResult, Part,
+        UploadedPart,
+    },
+};
+use url::Url;
+
+use crate::{
+    S3Error,
+    client::{BucketClient, S3ServiceError, http_client::HttpClient},
+    command::{
+        S3CommandBody,
+        put::{
+            AbortMultipartUpload, CompleteMultipartUpload, CreateMultipartUpload, ListParts,
+            PutPart,
+        },
+    },
+    credentials::presign::check_presign_expiration,
+};
+/// A multipart upload where the parts are uploaded with presigned urls, such as directly from a browser.
+///
+/// Started with [BucketClient::start_presigned_upload]. Keep [PresignedUpload::key] and [PresignedUpload::upload_id]
+/// to finish it with [BucketClient::complete_presigned_upload] or [BucketClient::abort_presigned_upload].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PresignedUpload {
+    pub key: String,
+    pub upload_id: String,
+    pub parts: Vec<PresignedPart>,
+}
+/// A presigned `PUT` url for one part of a [PresignedUpload].
+///
+/// The `ETag` header of the response has to be reported back. Browsers can only read it if the CORS rules of the bucket expose it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PresignedPart {
+    pub part_number: u32,
+    pub url: Url,
+}
+impl<Client: HttpClient> BucketClient<Client> {
+    /// Creates a multipart upload and presigns a [PutPart] url for the part numbers `1..=part_count`.
+    ///
+    /// Every part except the last one must be at least 5 MiB.
+    ///
+    /// Uploads with a [CreateMultipartUpload::checksum_algorithm] are refused with [S3Error::PresignedPartChecksum].
+    /// The checksum header of every part would have to be signed before the content is known.
+    pub async fn start_presigned_upload(
+        &self,
+        create: CreateMultipartUpload<'_>,
+        part_count: u32,
+        expires_in: Duration,
+    ) -> Result<PresignedUpload, S3Error> {
+        if !(1..=10000).contains(&part_count) {
+            return Err(S3Error::InvalidPartCount(part_count));
+        }
+        if let Some(algorithm) = create.checksum_algorithm {
+            return Err(S3Error::PresignedPartChecksum(algorithm));
+        }
+        // Checked before the upload is created so it is not left behind
+        check_presign_expiration(expires_in)?;
+        let response = self.execute_command(create).await?;
+        if !response.status().is_success() {
+            return Err(S3Error::from_response(response).await);
+        }
+        let initiate: InitiateMultipartUploadResult =
+            quick_xml::de::from_str(&response.text().await?).map_err(S3ContentError::from)?;
+        let parts = self
+            .presign_parts(
+                &initiate.key,
+                &initiate.upload_id,
+                1..=part_count,
+                expires_in,
+            )
+            .await?;
+        Ok(PresignedUpload {
+            key: initiate.key,
+            upload_id: initiate.upload_id,
+            parts,
+        })
+    }
+    /// Presigns [PutPart] urls for an upload. Use it to replace urls that expired.
+    pub async fn presign_parts(
+        &self,
+        key: &str,
+        upload_id: &str,
+        part_numbers: impl IntoIterator<Item = u32>,
+        expires_in: Duration,
+    ) -> Result<Vec<PresignedPart>, S3Error> {
+        let mut parts = Vec::new();
+        for part_number in part_numbers {
+            let put_part = PutPart {
+                key,
+                part_number,
+                upload_id: Cow::Borrowed(upload_id),
+                content: S3CommandBody::default(),
+                checksum_algorithm: None,
+                content_md5: false,
+            };
+            let url = self.presign(put_part, expires_in).await?;
+            parts.push(PresignedPart { part_number, url });
+        }
+        Ok(parts)
+    }
+    /// Returns a single page of the parts that have been uploaded
+    pub async fn list_parts(&self, list_parts: ListParts<'_>) -> Result<ListPartsResult, S3Error> {
+        let response = self.execute_command(list_parts).await?;
+        if !response.status().is_success() {
+            return Err(S3Error::from_response(response).await);
+        }
+        let result: ListPartsResult =
+            quick_xml::de::from_str(&response.text().await?).map_err(S3ContentError::from)?;
+        Ok(result)
+    }
+    /// Returns every part that has been uploaded, ordered by part number
+    pub async fn list_all_parts(
+        &self,
+        key: &str,
+        upload_id: &str,
+    ) -> Result<Vec<UploadedPart>, S3Error> {
+        let mut parts = Vec::new();
+        let mut part_number_marker = None;
+        loop {
+            let page = self
+                .list_parts(ListParts {
+                    key,
+                    upload_id: Cow::Borrowed(upload_id),
+                    max_parts: None,
+                    part_number_marker,
+                })
+                .await?;
+            parts.extend(page.parts);
+            match page.next_part_number_marker {
+                Some(next) if page.is_truncated => part_number_marker = Some(next),
+                _ => break,
+            }
+        }
+        Ok(parts)
+    }
+    /// Completes the upload with the parts that were reported by the uploader.
+    ///
+    /// The reported parts are checked against [BucketClient::list_all_parts] first. [S3Error::PartsMismatch] is returned
+    /// if a part is missing or has a different ETag. The upload is left open so the parts can be uploaded again.
+    pub async fn complete_presigned_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+        reported: Vec<Part>,
+    ) -> Result<CompleteMultipartUploadResult, S3Error> {
+        let uploaded = self.list_all_parts(key, upload_id).await?;
+        let parts = validate_reported_parts(reported, &uploaded)?;
+        let complete = CompleteMultipartUpload {
+            key,
+            upload_id: Cow::Borrowed(upload_id),
+            content: tux_io_s3_types::multi_part::CompleteMultipartUpload { parts },
+        };
+        let response = self.execute_command(complete).await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(S3Error::from_response(response).await);
+        }
+        let body = response.text().await?;
+        // S3 can report an error with a 200 once it started combining the parts
+        if let Ok(error) = quick_xml::de::from_str::<ErrorBody>(&body) {
+            return Err(S3ServiceError::new(status, error).into());
+        }
+        let result: CompleteMultipartUploadResult =
+            quick_xml::de::from_str(&body).map_err(S3ContentError::from)?;
+        Ok(result)
+    }
+    /// Aborts the upload and deletes the uploaded parts. The presigned urls stop working.
+    pub async fn abort_presigned_upload(&self, key: &str, upload_id: &str) -> Result<(), S3Error> {
+        let abort = AbortMultipartUpload {
+            key,
+            upload_id: Cow::Borrowed(upload_id),
+        };
+        let response = self.execute_command(abort).await?;
+        if !response.status().is_success() {
+            return Err(S3Error::from_response(response).await);
+        }
+        Ok(())
+    }
+}
+/// Sorts the reported parts and checks that every one of them was uploaded with the same ETag.
+fn validate_reported_parts(
+    mut reported: Vec<Part>,
+    uploaded: &[UploadedPart],
+) -> Result<Vec<Part>, S3Error> {
+    if reported.is_empty() {
+        return Err(S3Error::PartsMismatch("no parts were reported".to_owned()));
+    }
+    reported.sort_by_key(|part| part.number);
+    if let Some(pair) = reported
+        .windows(2)
+        .find(|pair| pair[0].number == pair[1].number)
+    {
+        return Err(S3Error::PartsMismatch(format!(
+            "part {} was reported more than once",
+            pair[0].number
+        )));
+    }
+    for part in &mut reported {
+        let Some(uploaded) = uploaded
+            .iter()
+            .find(|uploaded| uploaded.part_number == part.number)
+        else {
+            return Err(S3Error::PartsMismatch(format!(
+                "part {} was not uploaded",
+                part.number
+            )));
+        };
+        if uploaded.etag.trim_matches('"') != part.etag.trim_matches('"') {
+            return Err(S3Error::PartsMismatch(format!(
+                "ETag of part {} does not match",
+                part.number
+            )));
+        }
+        // The ETag as S3 formats it and the checksums S3 verified
+        *part = uploaded.clone().into();
+    }
+    Ok(reported)
+}
+
+#[cfg(test)]
+mod tests {
+    use http::header::ETAG;
+    use tux_io_s3_types::checksum::ChecksumAlgorithm;
+
+    use super::*;
+    use crate::{
+        command::put::PutHeaders,
+        test::{
+            IN_MEMORY_BUCKET, create_in_memory_bucket_client, generate_test_content,
+            init_test_logger,
+        },
+    };
+
+    #[tokio::test]
+    async fn browser_upload() -> anyhow::Result<()> {
+        init_test_logger();
+        let (s3, client) = create_in_memory_bucket_client();
+        let create = CreateMultipartUpload {
+            key: "uploads/large.bin",
+            tags: None,
+            headers: PutHeaders::default(),
+            checksum_algorithm: None,
+        };
+        let upload = client
+            .start_presigned_upload(create, 2, Duration::from_secs(600))
+            .await?;
+        assert_eq!(upload.parts.len(), 2);
+        assert_eq!(s3.pending_uploads(IN_MEMORY_BUCKET), 1);
+
+        let content = generate_test_content(5 * 1024 * 1024 + 100);
+        let (first, second) = content.split_at(5 * 1024 * 1024);
+        let mut reported = Vec::new();
+        // The browser uploads the parts in any order without credentials
+        for (part, body) in upload.parts.iter().zip([first, second]).rev() {
+            let request = s3.put(part.url.clone()).body(body.to_vec()).build()?;
+            let response = s3.execute(request).await?;
+            assert!(response.status().is_success());
+            reported.push(Part {
+                number: part.part_number,
+                etag: response.headers()[ETAG].to_str()?.to_owned(),
+                ..Default::default()
+            });
+        }
+        let uploaded = client
+            .list_all_parts(&upload.key, &upload.upload_id)
+            .await?;
+        assert_eq!(
+            uploaded.iter().map(|part| part.size).collect::<Vec<_>>(),
+            vec![first.len() as u64, second.len() as u64]
+        );
+
+        let mut wrong = reported.clone();
+        wrong[0].etag = "\"00000000000000000000000000000000\"".to_owned();
+        let result = client
+            .complete_presigned_upload(&upload.key, &upload.upload_id, wrong)
+            .await;
+        assert!(matches!(result, Err(S3Error::PartsMismatch(_))));
+
+        let result = client
+            .complete_presigned_upload(&upload.key, &upload.upload_id, reported)
+            .await?;
+        assert_eq!(result.key, "uploads/large.bin");
+        let object = s3
+            .get_object(IN_MEMORY_BUCKET, "uploads/large.bin")
+            .expect("Object was not stored");
+        assert_eq!(object.content, content);
+        assert_eq!(s3.pending_uploads(IN_MEMORY_BUCKET), 0);
+        Ok(())
+    }
+    #[tokio::test]
+    async fn abort() -> anyhow::Result<()> {
+        init_test_logger();
+        let (s3, client) = create_in_memory_bucket_client();
+        let create = CreateMultipartUpload {
+            key: "aborted.bin",
+            tags: None,
+            headers: PutHeaders::default(),
+            checksum_algorithm: None,
+        };
+        let result = client
+            .start_presigned_upload(create, 0, Duration::from_secs(600))
+            .await;
+        assert!(matches!(result, Err(S3Error::InvalidPartCount(0))));
+        assert_eq!(s3.pending_uploads(IN_MEMORY_BUCKET), 0);
+
+        let create = CreateMultipartUpload {
+            key: "aborted.bin",
+            tags: None,
+            headers: PutHeaders::default(),
+            checksum_algorithm: Some(ChecksumAlgorithm::Crc32),
+        };
+        let result = client
+            .start_presigned_upload(create, 3, Duration::from_secs(600))
+            .await;
+        assert!(matches!(
+            result,
+            Err(S3Error::PresignedPartChecksum(ChecksumAlgorithm::Crc32))
+        ));
+        assert_eq!(s3.pending_uploads(IN_MEMORY_BUCKET), 0);
+
+        let create = CreateMultipartUpload {
+            key: "aborted.bin",
+            tags: None,
+            headers: PutHeaders::default(),
+            checksum_algorithm: None,
+        };
+        let upload = client
+            .start_presigned_upload(create, 3, Duration::from_secs(600))
+            .await?;
+        client
+            .abort_presigned_upload(&upload.key, &upload.upload_id)
+            .await?;
+        assert_eq!(s3.pending_uploads(IN_MEMORY_BUCKET), 0);
+        Ok(())
+    }
+    #[test]
+    fn validate_parts() {
+        let uploaded: Vec<UploadedPart> = (1..=3)
+            .map(|number| UploadedPart {
+                part_number: number,
+                last_modified: chrono::Utc::now().fixed_offset(),
+                etag: format!("\"etag{number}\""),
+                size: 5,
+                ..Default::default()
+            })
+            .collect();
+        let part = |number: u32, etag: &str| Part {
+            number,
+            etag: etag.to_owned(),
+            ..Default::default()
+        };
+        let parts =
+            validate_reported_parts(vec![part(2, "etag2"), part(1, "\"etag1\"")], &uploaded)
+                .unwrap();
+        assert_eq!(parts, vec![part(1, "\"etag1\""), part(2, "\"etag2\"")]);
+
+        for reported in [
+            vec![],
+            vec![part(1, "etag1"), part(1, "etag1")],
+            vec![part(4, "etag4")],
+            vec![part(1, "etag2")],
+        ] {
+            assert!(validate_reported_parts(reported, &uploaded).is_err());
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use std::{fmt::Debug, sync::Arc, time::Duration};
+
+use http::StatusCode;
+use rand::Rng;
+use tux_io_s3_types::error::Error as ErrorBody;
+
+use crate::client::HttpResponseError;
+/// Status codes that are always considered transient.
+const RETRYABLE_STATUS_CODES: [StatusCode; 5] = [
+    StatusCode::TOO_MANY_REQUESTS,
+    StatusCode::INTERNAL_SERVER_ERROR,
+    StatusCode::BAD_GATEWAY,
+    StatusCode::SERVICE_UNAVAILABLE,
+    StatusCode::GATEWAY_TIMEOUT,
+];
+/// Decides if a failed attempt should be sent again.
+pub trait RetryClassifier: Debug + Send + Sync {
+    /// Returns true if the request that failed with `error` should be retried.
+    ///
+    /// `error_body` is the parsed `<Error>` document if the server sent one.
+    fn should_retry(&self, error: &HttpResponseError, error_body: Option<&ErrorBody>) -> bool;
+}
+/// Retries connection errors, timeouts, failed body reads, throttling, 5xx responses and [tux_io_s3_types::error::S3ErrorCode::is_transient] errors.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultRetryClassifier;
+impl RetryClassifier for DefaultRetryClassifier {
+    fn should_retry(&self, error: &HttpResponseError, error_body: Option<&ErrorBody>) -> bool {
+        match error {
+            HttpResponseError::ReqwestError(err) => {
+                err.is_connect()
+                    || err.is_timeout()
+                    || err.is_request()
+                    || err.is_body()
+                    || err.is_decode()
+            }
+            HttpResponseError::Response(response) => {
+                RETRYABLE_STATUS_CODES.contains(&response.status())
+                    || error_body.is_some_and(|body| body.error_code().is_transient())
+            }
+        }
+    }
+}
+/// How [crate::client::S3Client] and [crate::client::BucketClient] retry failed requests.
+///
+/// Only requests with a body that can be sent again are retried. Streaming bodies are sent once.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total number of attempts including the first one.
+    pub max_attempts: u32,
+    /// Backoff before the first retry. Doubled for every retry after.
+    pub initial_backoff: Duration,
+    /// Upper bound for a single backoff.
+    pub max_backoff: Duration,
+    /// Picks a random backoff between zero and the computed backoff.
+    pub jitter: bool,
+    pub classifier: Arc<dyn RetryClassifier>,
+}
+/// Up to 3 attempts with a jittered backoff starting at 100ms and the [DefaultRetryClassifier]
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(20),
+            jitter: true,
+            classifier: Arc::new(DefaultRetryClassifier),
+        }
+    }
+}
+impl RetryPolicy {
+    /// A policy that sends every request exactly once.
+    pub fn disabled() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+    pub fn with_backoff(mut self, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self.max_backoff = max_backoff;
+        self
+    }
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+    pub fn with_classifier(mut self, classifier: impl RetryClassifier + 'static) -> Self {
+        self.classifier = Arc::new(classifier);
+        self
+    }
+    /// Returns how long to wait after `attempt` failed.
+    ///
+    /// `attempt` starts at 1
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(1 << exponent)
+            .min(self.max_backoff);
+        if self.jitter && !backoff.is_zero() {
+            rand::rng().random_range(Duration::ZERO..=backoff)
+        } else {
+            backoff
+        }
+    }
+    pub(crate) fn should_retry(
+        &self,
+        attempt: u32,
+        error: &HttpResponseError,
+        error_body: Option<&ErrorBody>,
+    ) -> bool {
+        attempt < self.max_attempts && self.classifier.should_retry(error, error_body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn response(status: StatusCode) -> HttpResponseError {
+        let response = http::Response::builder()
+            .status(status)
+            .body(bytes::Bytes::new())
+            .unwrap();
+        HttpResponseError::Response(reqwest::Response::from(response))
+    }
+    fn error_body(code: &str) -> ErrorBody {
+        ErrorBody {
+            code: code.to_string(),
+            message: None,
+            request_id: None,
+            host_id: None,
+            attributes: Default::default(),
+        }
+    }
+    #[test]
+    fn backoff_grows_and_caps() {
+        let policy = RetryPolicy::default()
+            .with_jitter(false)
+            .with_backoff(Duration::from_millis(100), Duration::from_millis(350));
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(350));
+        assert_eq!(policy.backoff(40), Duration::from_millis(350));
+    }
+    #[test]
+    fn jitter_stays_within_backoff() {
+        let policy =
+            RetryPolicy::default().with_backoff(Duration::from_millis(100), Duration::from_secs(1));
+        for attempt in 1..5 {
+            assert!(policy.backoff(attempt) <= Duration::from_millis(100) * (1 << (attempt - 1)));
+        }
+    }
+    #[test]
+    fn default_classifier() {
+        let classifier = DefaultRetryClassifier;
+        assert!(classifier.should_retry(&response(StatusCode::SERVICE_UNAVAILABLE), None));
+        assert!(classifier.should_retry(&response(StatusCode::INTERNAL_SERVER_ERROR), None));
+        assert!(!classifier.should_retry(&response(StatusCode::NOT_FOUND), None));
+        assert!(classifier.should_retry(
+            &response(StatusCode::BAD_REQUEST),
+            Some(&error_body("RequestTimeout"))
+        ));
+        assert!(!classifier.should_retry(
+            &response(StatusCode::BAD_REQUEST),
+            Some(&error_body("InvalidArgument"))
+        ));
+    }
+    #[test]
+    fn max_attempts() {
+        let policy = RetryPolicy::default().with_max_attempts(2);
+        let error = response(StatusCode::SERVICE_UNAVAILABLE);
+        assert!(policy.should_retry(1, &error, None));
+        assert!(!policy.should_retry(2, &error, None));
+        assert!(!RetryPolicy::disabled().should_retry(1, &error, None));
+    }
+}
@@ -1,12 +1,120 @@
+use std::borrow::Cow;
+
 use serde::{Deserialize, Serialize};
+use tux_io_s3_types::region::{OfficialRegion, Partition, RegionType, S3Region};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AccessType {
     PathStyle,
     VirtualHostedStyle,
 }
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// How the body of a request is covered by the signature.
+///
+/// Set for a client with [crate::client::S3ClientBuilder::with_payload_signing] and for a single command with
+/// [crate::command::S3CommandBody::with_payload_signing].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PayloadSigning {
+    /// The SHA-256 of fixed bodies is signed. Streams can not be hashed upfront and are signed chunk by chunk.
+    #[default]
+    FullHash,
+    /// Every body is sent `aws-chunked` and each chunk is signed. `STREAMING-AWS4-HMAC-SHA256-PAYLOAD`
+    Streaming,
+    /// The body is not signed. `UNSIGNED-PAYLOAD`
+    ///
+    /// Saves hashing the body when TLS already protects it. Refused over plain HTTP unless
+    /// [crate::client::S3ClientBuilder::with_unsigned_payload_over_http] is set.
+    /// Streams with a checksum are sent `STREAMING-UNSIGNED-PAYLOAD-TRAILER`.
+    Unsigned,
+}
+/// Which ListObjects API is used by [crate::client::BucketClient::list_objects_stream]
+///
+/// Set with [crate::client::S3ClientBuilder::with_list_objects_version].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ListObjectsVersion {
+    /// For endpoints that do not support V2. Pages are requested with markers.
     V1,
+    #[default]
     V2,
+    /// Probes the endpoint once with a V2 request and falls back to V1 if V2 is not recognized.
+    Auto,
+}
+/// Alternative endpoints of official regions.
+///
+/// Set with [crate::client::S3ClientBuilder::with_endpoint_variants]. Custom regions do not support variants.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndpointVariants {
+    /// Use the endpoints that support IPv4 and IPv6. `s3.dualstack.{region}.amazonaws.com`
+    #[serde(default)]
+    pub dual_stack: bool,
+    /// Use FIPS 140 validated endpoints. `s3-fips.{region}.amazonaws.com`
+    #[serde(default)]
+    pub fips: bool,
+    /// Use S3 Transfer Acceleration. `s3-accelerate.amazonaws.com`
+    ///
+    /// Requires [AccessType::VirtualHostedStyle]. Requests without a bucket use the regional endpoint.
+    #[serde(default)]
+    pub accelerate: bool,
+}
+impl EndpointVariants {
+    pub fn with_dual_stack(mut self, dual_stack: bool) -> Self {
+        self.dual_stack = dual_stack;
+        self
+    }
+    pub fn with_fips(mut self, fips: bool) -> Self {
+        self.fips = fips;
+        self
+    }
+    pub fn with_accelerate(mut self, accelerate: bool) -> Self {
+        self.accelerate = accelerate;
+        self
+    }
+    /// Returns true if no variant is enabled
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+    /// The variants for requests without a bucket. Transfer Acceleration only serves bucket requests.
+    pub(crate) fn without_bucket(&self) -> Self {
+        Self {
+            accelerate: false,
+            ..*self
+        }
+    }
+    /// Returns an error message if the variants can not be used with the region and access type
+    pub(crate) fn validate(
+        &self,
+        region: &S3Region,
+        access_type: AccessType,
+    ) -> Option<&'static str> {
+        let S3Region::Official(region) = region else {
+            return (!self.is_default()).then_some("Endpoint variants require an official region");
+        };
+        if !self.accelerate {
+            return None;
+        }
+        if access_type == AccessType::PathStyle {
+            Some("Transfer Acceleration requires virtual hosted style access")
+        } else if self.fips {
+            Some("Transfer Acceleration does not support FIPS endpoints")
+        } else if region.partition() != Partition::Aws {
+            Some("Transfer Acceleration is only available in the aws partition")
+        } else {
+            None
+        }
+    }
+    /// Returns the host of the region with the variants applied
+    pub(crate) fn host<'region>(&self, region: &'region OfficialRegion) -> Cow<'region, str> {
+        if self.is_default() {
+            return Cow::Borrowed(region.endpoint());
+        }
+        let dual_stack = if self.dual_stack { ".dualstack" } else { "" };
+        let dns_suffix = region.partition().dns_suffix();
+        if self.accelerate {
+            return Cow::Owned(format!("s3-accelerate{dual_stack}.{dns_suffix}"));
+        }
+        let service = if self.fips { "s3-fips" } else { "s3" };
+        Cow::Owned(format!(
+            "{service}{dual_stack}.{}.{dns_suffix}",
+            region.name()
+        ))
+    }
 }
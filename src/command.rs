@@ -18,6 +18,7 @@ use crate::S3Error;
 pub use body::S3CommandBody;
 
 pub trait CommandType: Sized {
+    /// The S3 operation, such as `GetObject`. Defaults to the type name
     fn name(&self) -> &'static str {
         std::any::type_name::<Self>()
     }
@@ -26,6 +27,12 @@ pub trait CommandType: Sized {
     fn metadata_is_invalid(&self) -> bool {
         false
     }
+    /// If a `Content-MD5` of the body is sent with the request.
+    ///
+    /// Only possible for bodies that are not streamed.
+    fn requires_content_md5(&self) -> bool {
+        false
+    }
     fn update_url(&self, _url: &mut Url) -> Result<(), S3Error> {
         Ok(())
     }
@@ -1,17 +1,23 @@
 use std::pin::Pin;
 
+use base64::{Engine, engine::general_purpose::STANDARD};
 use bytes::{Bytes, BytesMut};
 use chrono::{DateTime, Utc};
 use futures::{Stream, TryStream, TryStreamExt};
+use http::{HeaderMap, HeaderValue};
 use serde::Serialize;
 use tokio::io::AsyncRead;
 use tokio_util::io::ReaderStream;
 use tracing::trace;
-use tux_io_s3_types::{S3ContentError, Service};
+use tux_io_s3_types::{
+    S3ContentError, Service, checksum::ChecksumAlgorithm, headers::X_AMZ_SDK_CHECKSUM_ALGORITHM,
+};
 mod stream;
 use crate::{
     EMPTY_HASH, S3Error,
+    client::PayloadSigning,
     credentials::signing::CHRONO_SHORT_DATE_FORMAT,
+    utils::checksum,
     utils::{
         LONG_DATE_FORMAT,
         stream::{DynMinSizedStream, MinimumSizedStream, S3_MINIMUM_SIZE, S3_RECOMMENDED_SIZE},
@@ -20,12 +26,13 @@ use crate::{
 pub use stream::*;
 pub struct S3CommandBody {
     pub(crate) inner: S3CommandBodyInner,
+    /// Overrides the [PayloadSigning] of the client for this body
+    pub(crate) payload_signing: Option<PayloadSigning>,
+    pub(crate) checksum: Option<ChecksumAlgorithm>,
 }
 impl Default for S3CommandBody {
     fn default() -> Self {
-        S3CommandBody {
-            inner: S3CommandBodyInner::None,
-        }
+        S3CommandBody::from_inner(S3CommandBodyInner::None)
     }
 }
 pub(crate) enum S3CommandBodyInner {
@@ -58,6 +65,7 @@ impl S3CommandBodyInner {
             } => Ok(FixedStream::Stream {
                 stream,
                 content_length,
+                trailer: None,
             }),
             S3CommandBodyInner::None => Ok(FixedStream::None),
 
@@ -85,9 +93,7 @@ macro_rules! into_fixed_size {
         $(
             impl From<$type> for S3CommandBody {
                 fn from(value: $type) -> Self {
-                    S3CommandBody {
-                        inner: S3CommandBodyInner::FixedContent(Bytes::from(value)),
-                    }
+                    S3CommandBody::from_inner(S3CommandBodyInner::FixedContent(Bytes::from(value)))
                 }
             }
         )*
@@ -105,19 +111,127 @@ pub(crate) enum FixedStream {
         /// Must be based on a
         stream: DynMinSizedStream,
         content_length: usize,
+        /// Checksum sent as a trailing header after the final chunk
+        trailer: Option<ChecksumAlgorithm>,
     },
     /// No Body
     None,
 }
+impl FixedStream {
+    /// Adds the `x-amz-checksum-*` header for fixed content. Streams send the checksum as a trailer.
+    pub(crate) fn with_checksum(
+        self,
+        algorithm: ChecksumAlgorithm,
+        headers: &mut HeaderMap,
+    ) -> Result<Self, S3Error> {
+        headers.insert(
+            X_AMZ_SDK_CHECKSUM_ALGORITHM,
+            HeaderValue::from_static(algorithm.as_str()),
+        );
+        match self {
+            FixedStream::Stream {
+                stream,
+                content_length,
+                ..
+            } => Ok(FixedStream::Stream {
+                stream,
+                content_length,
+                trailer: Some(algorithm),
+            }),
+            FixedStream::FixedContent(ref bytes) => {
+                let checksum = checksum::checksum(algorithm, bytes);
+                headers.insert(algorithm.header_name(), HeaderValue::from_str(&checksum)?);
+                Ok(self)
+            }
+            FixedStream::None => {
+                let checksum = checksum::checksum(algorithm, &[]);
+                headers.insert(algorithm.header_name(), HeaderValue::from_str(&checksum)?);
+                Ok(self)
+            }
+        }
+    }
+    /// The base64 encoded MD5 digest of the body for the `Content-MD5` header
+    pub(crate) fn content_md5(&self) -> Result<HeaderValue, S3Error> {
+        let digest = match self {
+            FixedStream::FixedContent(bytes) => md5::compute(bytes),
+            FixedStream::None => md5::compute(b""),
+            FixedStream::Stream { .. } => return Err(S3Error::ContentMd5ForStream),
+        };
+        Ok(HeaderValue::from_str(&STANDARD.encode(digest.0))?)
+    }
+    /// Returns a copy of the body if it can be sent more than once.
+    ///
+    /// Streams can only be read once so they return None.
+    pub(crate) fn try_clone(&self) -> Option<Self> {
+        match self {
+            FixedStream::FixedContent(bytes) => Some(FixedStream::FixedContent(bytes.clone())),
+            FixedStream::None => Some(FixedStream::None),
+            FixedStream::Stream { .. } => None,
+        }
+    }
+    /// Turns fixed content into a stream of [S3_RECOMMENDED_SIZE] chunks so it can be sent `aws-chunked`
+    pub(crate) fn into_chunked(self) -> Self {
+        let FixedStream::FixedContent(bytes) = self else {
+            return self;
+        };
+        let content_length = bytes.len();
+        let chunks: Vec<Result<Bytes, Box<dyn std::error::Error + Send + Sync>>> = (0
+            ..content_length)
+            .step_by(S3_RECOMMENDED_SIZE)
+            .map(|start| Ok(bytes.slice(start..(start + S3_RECOMMENDED_SIZE).min(content_length))))
+            .collect();
+        let stream: DynStream = Box::pin(futures::stream::iter(chunks));
+        FixedStream::Stream {
+            stream: MinimumSizedStream::new(stream).with_minimum_size(S3_RECOMMENDED_SIZE),
+            content_length,
+            trailer: None,
+        }
+    }
+    /// The body as it is sent without chunk signatures
+    pub(crate) fn into_plain_body(self) -> Option<reqwest::Body> {
+        match self {
+            FixedStream::FixedContent(bytes) => Some(reqwest::Body::from(bytes)),
+            FixedStream::Stream { stream, .. } => Some(reqwest::Body::wrap_stream(stream)),
+            FixedStream::None => None,
+        }
+    }
+    /// Number of bytes that are sent.
+    pub(crate) fn content_length(&self) -> usize {
+        match self {
+            FixedStream::FixedContent(bytes) => bytes.len(),
+            FixedStream::Stream { content_length, .. } => *content_length,
+            FixedStream::None => 0,
+        }
+    }
+}
 type DynStream =
     Pin<Box<dyn Stream<Item = Result<Bytes, Box<dyn std::error::Error + Send + Sync>>> + Send>>;
 impl S3CommandBody {
+    fn from_inner(inner: S3CommandBodyInner) -> Self {
+        S3CommandBody {
+            inner,
+            payload_signing: None,
+            checksum: None,
+        }
+    }
+    /// Sends a checksum of the body that S3 verifies before storing it.
+    ///
+    /// Fixed content sends it in a `x-amz-checksum-*` header. Streams send it as a trailer.
+    pub fn with_checksum(mut self, algorithm: ChecksumAlgorithm) -> Self {
+        self.checksum = Some(algorithm);
+        self
+    }
+    /// Signs this body with `payload_signing` instead of the [PayloadSigning] of the client
+    pub fn with_payload_signing(mut self, payload_signing: PayloadSigning) -> Self {
+        self.payload_signing = Some(payload_signing);
+        self
+    }
     /// Serializes Content
     pub fn xml_content<S: Serialize>(content: &S) -> Result<Self, S3Error> {
         let xml = quick_xml::se::to_string(content).map_err(S3ContentError::from)?;
-        Ok(S3CommandBody {
-            inner: S3CommandBodyInner::FixedContent(Bytes::from(xml)),
-        })
+        Ok(S3CommandBody::from_inner(S3CommandBodyInner::FixedContent(
+            Bytes::from(xml),
+        )))
     }
     // TODO: Replace wrap_stream_with_chunk_size and wrap_reader_with_chunk_size with default a more direct Reader to Stream Impl that handles the size buffering
     /// Wraps a Stream in a [ReaderStream] then passes it into [Self::wrap_stream_with_chunk_size] with [S3_RECOMMENDED_SIZE]
@@ -175,8 +289,7 @@ impl S3CommandBody {
             }
         };
 
-        let result = S3CommandBody { inner };
-        Ok(result)
+        Ok(S3CommandBody::from_inner(inner))
     }
 }
 fn create_payload_signature(
@@ -202,3 +315,20 @@ fn create_payload_signature(
     );
     content
 }
+/// The string to sign for the `x-amz-trailer-signature` of an `aws-chunked` body
+fn create_trailer_signature(
+    date_time: DateTime<Utc>,
+    previous_signature: &str,
+    region: &str,
+    service: &Service,
+    trailer_hash: &str,
+) -> String {
+    let scope = format!(
+        "{date}/{region}/{service}/aws4_request",
+        date = date_time.format(CHRONO_SHORT_DATE_FORMAT),
+    );
+    format!(
+        "AWS4-HMAC-SHA256-TRAILER\n{timestamp}\n{scope}\n{previous_signature}\n{trailer_hash}",
+        timestamp = date_time.format(LONG_DATE_FORMAT),
+    )
+}
@@ -7,14 +7,36 @@ use std::{
     pin::Pin,
     task::{Context, Poll},
 };
-use tux_io_s3_types::Service;
+use tux_io_s3_types::{Service, checksum::ChecksumAlgorithm};
 
 use crate::{
     EMPTY_HASH,
-    command::body::create_payload_signature,
+    command::body::{create_payload_signature, create_trailer_signature},
     credentials::{error::SigningRelatedError, sha256_from_bytes, sign_content},
-    utils::stream::MinimumSizedStream,
+    utils::{checksum::ChecksumHasher, stream::MinimumSizedStream},
 };
+/// How the chunks of an `aws-chunked` body are signed. Sent as the `x-amz-content-sha256` of the request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamingPayload {
+    /// Every chunk is signed. `STREAMING-AWS4-HMAC-SHA256-PAYLOAD`
+    Signed,
+    /// Every chunk and the trailer are signed. `STREAMING-AWS4-HMAC-SHA256-PAYLOAD-TRAILER`
+    SignedTrailer,
+    /// Neither the chunks nor the trailer are signed. `STREAMING-UNSIGNED-PAYLOAD-TRAILER`
+    UnsignedTrailer,
+}
+impl StreamingPayload {
+    pub fn content_sha256(&self) -> &'static str {
+        match self {
+            StreamingPayload::Signed => "STREAMING-AWS4-HMAC-SHA256-PAYLOAD",
+            StreamingPayload::SignedTrailer => "STREAMING-AWS4-HMAC-SHA256-PAYLOAD-TRAILER",
+            StreamingPayload::UnsignedTrailer => "STREAMING-UNSIGNED-PAYLOAD-TRAILER",
+        }
+    }
+    pub fn is_signed(&self) -> bool {
+        !matches!(self, StreamingPayload::UnsignedTrailer)
+    }
+}
 #[pin_project]
 pub struct S3ContentStream<
     E: Into<Box<dyn Error + Send + Sync>>,
@@ -28,6 +50,9 @@ pub struct S3ContentStream<
     pub service: Service,
     pub signing_key: Vec<u8>,
     pub sent_final_chunk: bool,
+    pub payload: StreamingPayload,
+    /// Checksum of the content that is sent as a trailing header after the final chunk
+    pub trailer: Option<ChecksumHasher>,
 }
 impl<E: Into<Box<dyn Error + Send + Sync>>, S: Stream<Item = Result<Bytes, E>>>
     S3ContentStream<E, S>
@@ -48,8 +73,30 @@ impl<E: Into<Box<dyn Error + Send + Sync>>, S: Stream<Item = Result<Bytes, E>>>
             service,
             signing_key,
             sent_final_chunk: false,
+            payload: StreamingPayload::Signed,
+            trailer: None,
+        }
+    }
+    /// Sends the chunks without signatures. The checksum is calculated while streaming and sent as a trailer.
+    pub fn unsigned(stream: MinimumSizedStream<E, S>, algorithm: ChecksumAlgorithm) -> Self {
+        Self {
+            stream,
+            time: DateTime::default(),
+            previous_signature: String::new(),
+            region: String::new(),
+            service: Service::S3,
+            signing_key: Vec::new(),
+            sent_final_chunk: false,
+            payload: StreamingPayload::UnsignedTrailer,
+            trailer: Some(ChecksumHasher::new(algorithm)),
         }
     }
+    /// Sends a checksum of the content as a signed trailer.
+    pub fn with_trailer(mut self, algorithm: ChecksumAlgorithm) -> Self {
+        self.payload = StreamingPayload::SignedTrailer;
+        self.trailer = Some(ChecksumHasher::new(algorithm));
+        self
+    }
 }
 #[derive(Debug, thiserror::Error)]
 pub enum S3ContentStreamError {
@@ -58,6 +105,21 @@ pub enum S3ContentStreamError {
     #[error(transparent)]
     SigningRelatedError(SigningRelatedError),
 }
+/// Signs a chunk and replaces `previous_signature` with the new signature
+fn sign_chunk(
+    time: DateTime<Utc>,
+    previous_signature: &mut String,
+    region: &str,
+    service: &Service,
+    signing_key: &[u8],
+    content_hash: &str,
+) -> Result<String, S3ContentStreamError> {
+    let content = create_payload_signature(time, previous_signature, region, service, content_hash);
+    let signature =
+        sign_content(&content, signing_key).map_err(S3ContentStreamError::SigningRelatedError)?;
+    previous_signature.clone_from(&signature);
+    Ok(signature)
+}
 
 impl<E: Into<Box<dyn Error + Send + Sync>>, S: Stream<Item = Result<Bytes, E>>> Stream
     for S3ContentStream<E, S>
@@ -68,59 +130,87 @@ impl<E: Into<Box<dyn Error + Send + Sync>>, S: Stream<Item = Result<Bytes, E>>>
         let this = self.project();
         match this.stream.poll_next(cx) {
             Poll::Ready(Some(Ok(data))) => {
-                let hash = sha256_from_bytes(&data);
-                let content = create_payload_signature(
-                    *this.time,
-                    this.previous_signature,
-                    this.region,
-                    this.service,
-                    &hash,
-                );
-                let signature = match sign_content(&content, this.signing_key) {
-                    Ok(ok) => ok,
-                    Err(err) => {
-                        return Poll::Ready(Some(Err(S3ContentStreamError::SigningRelatedError(
-                            err,
-                        ))));
-                    }
-                };
-                let mut actual_content = BytesMut::with_capacity(data.len() + 64);
-                actual_content
-                    .extend_from_slice(format!("{:x};chunk-signature=", data.len()).as_bytes());
-                actual_content.extend_from_slice(signature.as_bytes());
+                if let Some(trailer) = this.trailer {
+                    trailer.update(&data);
+                }
+                let mut actual_content = BytesMut::with_capacity(data.len() + 96);
+                actual_content.extend_from_slice(format!("{:x}", data.len()).as_bytes());
+                if this.payload.is_signed() {
+                    let signature = match sign_chunk(
+                        *this.time,
+                        this.previous_signature,
+                        this.region,
+                        this.service,
+                        this.signing_key,
+                        &sha256_from_bytes(&data),
+                    ) {
+                        Ok(ok) => ok,
+                        Err(err) => return Poll::Ready(Some(Err(err))),
+                    };
+                    actual_content.extend_from_slice(b";chunk-signature=");
+                    actual_content.extend_from_slice(signature.as_bytes());
+                }
                 actual_content.extend_from_slice(b"\r\n");
                 actual_content.extend_from_slice(data.as_ref());
                 actual_content.extend_from_slice(b"\r\n");
-                *this.previous_signature = signature;
                 Poll::Ready(Some(Ok(actual_content.freeze())))
             }
             Poll::Ready(Some(Err(e))) => {
                 Poll::Ready(Some(Err(S3ContentStreamError::InternalError(e.into()))))
             }
             Poll::Ready(None) => {
-                if !*this.sent_final_chunk {
-                    *this.sent_final_chunk = true;
-                    let content = create_payload_signature(
+                if *this.sent_final_chunk {
+                    return Poll::Ready(None);
+                }
+                *this.sent_final_chunk = true;
+                let mut final_chunk = String::from("0");
+                if this.payload.is_signed() {
+                    let signature = match sign_chunk(
                         *this.time,
                         this.previous_signature,
                         this.region,
                         this.service,
+                        this.signing_key,
                         EMPTY_HASH,
-                    );
-                    let signature = match sign_content(&content, this.signing_key) {
+                    ) {
                         Ok(ok) => ok,
-                        Err(err) => {
-                            return Poll::Ready(Some(Err(
-                                S3ContentStreamError::SigningRelatedError(err),
-                            )));
-                        }
+                        Err(err) => return Poll::Ready(Some(Err(err))),
                     };
-                    let content = format!("0;chunk-signature={}\r\n\r\n", signature);
-                    let final_chunk = Bytes::from(content);
-                    Poll::Ready(Some(Ok(final_chunk)))
-                } else {
-                    Poll::Ready(None)
+                    final_chunk.push_str(";chunk-signature=");
+                    final_chunk.push_str(&signature);
+                }
+                final_chunk.push_str("\r\n");
+                if let Some(trailer) = this.trailer.take() {
+                    let trailing_header = format!(
+                        "{}:{}",
+                        trailer.algorithm().header_name(),
+                        trailer.finalize()
+                    );
+                    final_chunk.push_str(&trailing_header);
+                    final_chunk.push_str("\r\n");
+                    if this.payload.is_signed() {
+                        let content = create_trailer_signature(
+                            *this.time,
+                            this.previous_signature,
+                            this.region,
+                            this.service,
+                            &sha256_from_bytes(format!("{trailing_header}\n").as_bytes()),
+                        );
+                        let trailer_signature = match sign_content(&content, this.signing_key) {
+                            Ok(ok) => ok,
+                            Err(err) => {
+                                return Poll::Ready(Some(Err(
+                                    S3ContentStreamError::SigningRelatedError(err),
+                                )));
+                            }
+                        };
+                        final_chunk.push_str("x-amz-trailer-signature:");
+                        final_chunk.push_str(&trailer_signature);
+                        final_chunk.push_str("\r\n");
+                    }
                 }
+                final_chunk.push_str("\r\n");
+                Poll::Ready(Some(Ok(Bytes::from(final_chunk))))
             }
             Poll::Pending => Poll::Pending,
         }
@@ -131,3 +221,113 @@ impl<E: Into<Box<dyn Error + Send + Sync>>, S: Stream<Item = Result<Bytes, E>>>
         (low, None)
     }
 }
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use chrono::TimeZone;
+    use futures::TryStreamExt;
+    use tux_io_s3_types::region::{OfficialRegion, S3Region};
+
+    use super::*;
+    use crate::{credentials::signing::SigningKey, utils::checksum::checksum};
+    /// The streaming upload of the AWS documentation. 64 KiB and 1 KiB of `a`.
+    ///
+    /// [AWS Docs](https://docs.aws.amazon.com/AmazonS3/latest/API/sigv4-streaming.html)
+    async fn aws_example(
+        seed_signature: &str,
+        trailer: Option<ChecksumAlgorithm>,
+    ) -> anyhow::Result<Vec<u8>> {
+        let time = Utc.with_ymd_and_hms(2013, 5, 24, 0, 0, 0).unwrap();
+        let signing_key = SigningKey {
+            secret_key: Cow::Borrowed("wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY"),
+            date_time: time,
+            region: Cow::Owned(S3Region::Official(OfficialRegion::UsEast1)),
+            service: Service::S3,
+        }
+        .key()?;
+        let chunks = futures::stream::iter([
+            Ok::<_, std::io::Error>(Bytes::from(vec![b'a'; 65536])),
+            Ok(Bytes::from(vec![b'a'; 1024])),
+        ]);
+        let stream = S3ContentStream::new(
+            MinimumSizedStream::new(chunks).with_minimum_size(65536),
+            time,
+            seed_signature.to_owned(),
+            "us-east-1".to_owned(),
+            Service::S3,
+            signing_key,
+        );
+        let stream = match trailer {
+            Some(algorithm) => stream.with_trailer(algorithm),
+            None => stream,
+        };
+        let body: Vec<Bytes> = stream.try_collect().await?;
+        Ok(body.concat())
+    }
+    fn aws_example_chunks(signatures: [&str; 2]) -> Vec<u8> {
+        let mut expected = Vec::new();
+        for (size, signature) in [(65536, signatures[0]), (1024, signatures[1])] {
+            expected
+                .extend_from_slice(format!("{size:x};chunk-signature={signature}\r\n").as_bytes());
+            expected.extend_from_slice(&vec![b'a'; size]);
+            expected.extend_from_slice(b"\r\n");
+        }
+        expected
+    }
+    #[tokio::test]
+    async fn aws_signed_chunks() -> anyhow::Result<()> {
+        let body = aws_example(
+            "4f232c4386841ef735655705268965c44a0e4690baa4adea153f7db9fa80a0a9",
+            None,
+        )
+        .await?;
+        let mut expected = aws_example_chunks([
+            "ad80c730a21e5b8d04586a2213dd63b9a0e99e0e2307b0ade35a65485a288648",
+            "0055627c9e194cb4542bae2aa5492e3c1575bbb81b612b7d234b86a503ef5497",
+        ]);
+        expected.extend_from_slice(
+            b"0;chunk-signature=b6c6ea8a5354eaf15b3cb7646744f4275b71ea724fed81ceb9323e279d449df9\r\n\r\n",
+        );
+        assert_eq!(body, expected);
+        Ok(())
+    }
+    /// [AWS Docs](https://docs.aws.amazon.com/AmazonS3/latest/API/sigv4-streaming-trailers.html)
+    #[tokio::test]
+    async fn aws_signed_trailer() -> anyhow::Result<()> {
+        let body = aws_example(
+            "106e2a8a18243abcf37539882f36619c00e2dfc72633413f02d3b74544bfeb8e",
+            Some(ChecksumAlgorithm::Crc32c),
+        )
+        .await?;
+        let mut expected = aws_example_chunks([
+            "b474d8862b1487a5145d686f57f013e54db672cee1c953b3010fb58501ef5aa2",
+            "1c1344b170168f8e65b41376b44b20fe354e373826ccbbe2c1d40a8cae51e5c7",
+        ]);
+        expected.extend_from_slice(
+            b"0;chunk-signature=2ca2aba2005185cf7159c6277faf83795951dd77a3a99e6e65d5c9f85863f992\r\n\
+            x-amz-checksum-crc32c:sOO8/Q==\r\n\
+            x-amz-trailer-signature:d81f82fc3505edab99d459891051a732e8730629a2e4a59689829ca17fe2e435\r\n\r\n",
+        );
+        assert_eq!(body, expected);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn unsigned_trailer() -> anyhow::Result<()> {
+        let chunks = futures::stream::iter([
+            Ok::<_, std::io::Error>(Bytes::from_static(b"Hello ")),
+            Ok(Bytes::from_static(b"World")),
+        ]);
+        let stream = MinimumSizedStream::new(chunks).with_minimum_size(8);
+        let body: Vec<Bytes> = S3ContentStream::unsigned(stream, ChecksumAlgorithm::Crc32)
+            .try_collect()
+            .await?;
+        let expected = format!(
+            "b\r\nHello World\r\n0\r\nx-amz-checksum-crc32:{}\r\n\r\n",
+            checksum(ChecksumAlgorithm::Crc32, b"Hello World")
+        );
+        assert_eq!(body.concat(), expected.as_bytes());
+        Ok(())
+    }
+}
@@ -1,4 +1,6 @@
+mod objects;
 mod tagging;
+pub use objects::*;
 pub use tagging::*;
 
 use crate::{
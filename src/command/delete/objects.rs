@@ -0,0 +1,80 @@
+use http::{HeaderMap, Method};
+use tux_io_s3_types::delete::Delete;
+use url::Url;
+
+use crate::{
+    S3Error,
+    command::{BucketCommandType, CommandType, S3CommandBody},
+    utils::{XML_HEADER_VALUE, header::HeaderMapS3Ext},
+};
+/// Deletes up to 1000 objects with one request.
+///
+/// S3 refuses the request without a `Content-MD5` so it is always sent.
+#[derive(Debug, Clone, Default)]
+pub struct DeleteObjects {
+    pub content: Delete,
+}
+impl CommandType for DeleteObjects {
+    fn name(&self) -> &'static str {
+        "DeleteObjects"
+    }
+    fn http_method(&self) -> Method {
+        Method::POST
+    }
+    fn requires_content_md5(&self) -> bool {
+        true
+    }
+    fn update_url(&self, url: &mut Url) -> Result<(), S3Error> {
+        url.query_pairs_mut().append_key_only("delete");
+        Ok(())
+    }
+    fn headers(&self, base: &mut HeaderMap) -> Result<(), S3Error> {
+        base.content_type(XML_HEADER_VALUE);
+        Ok(())
+    }
+    fn into_body(self) -> Result<S3CommandBody, S3Error> {
+        S3CommandBody::xml_content(&self.content)
+    }
+}
+impl BucketCommandType for DeleteObjects {}
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+    use tux_io_s3_types::delete::Delete;
+
+    use crate::{
+        client::http_client::HttpClient,
+        test::{IN_MEMORY_BUCKET, create_in_memory_bucket_client, init_test_logger},
+    };
+
+    #[tokio::test]
+    async fn delete_objects() -> anyhow::Result<()> {
+        init_test_logger();
+        let (s3, client) = create_in_memory_bucket_client();
+        for key in ["a.txt", "b.txt", "c.txt"] {
+            s3.put_object(IN_MEMORY_BUCKET, key, "content");
+        }
+        // The server refuses the request without a Content-MD5 so this only passes if it was sent
+        let result = client
+            .delete_objects(Delete::new(["a.txt", "b.txt"]))
+            .await?;
+        let deleted: Vec<_> = result
+            .deleted
+            .iter()
+            .map(|object| object.key.as_str())
+            .collect();
+        assert_eq!(deleted, vec!["a.txt", "b.txt"]);
+        assert!(result.errors.is_empty());
+        assert!(s3.get_object(IN_MEMORY_BUCKET, "a.txt").is_none());
+        assert!(s3.get_object(IN_MEMORY_BUCKET, "c.txt").is_some());
+
+        let request = s3
+            .post(format!("http://localhost:9000/{IN_MEMORY_BUCKET}/?delete").parse::<url::Url>()?)
+            .body("<Delete><Object><Key>c.txt</Key></Object></Delete>")
+            .build()?;
+        let response = s3.execute(request).await?;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert!(s3.get_object(IN_MEMORY_BUCKET, "c.txt").is_some());
+        Ok(())
+    }
+}
@@ -1,12 +1,18 @@
+use bytes::{Bytes, BytesMut};
+use futures::{Stream, TryStreamExt, future::Either};
 use http::{
     HeaderName, HeaderValue, Method,
     header::{ACCEPT, CONTENT_LENGTH, RANGE},
 };
+use tux_io_s3_types::{
+    checksum::ChecksumAlgorithm,
+    headers::{X_AMZ_CHECKSUM_MODE, X_AMZ_CHECKSUM_TYPE},
+};
 mod tagging;
 use crate::{
-    InvalidResponseHeader,
+    InvalidResponseHeader, S3Error,
     command::{BucketCommandType, CommandType},
-    utils::url::S3UrlExt,
+    utils::{checksum::ChecksumStream, url::S3UrlExt},
 };
 pub use tagging::*;
 #[derive(Debug, Clone, Copy)]
@@ -67,14 +73,61 @@ impl GetObjectResponse {
             Ok(header.to_str().map_err(Box::new)?.into())
         })
     }
+    /// The checksum of the object. Only returned for requests with [GetObject::checksum_mode].
+    ///
+    /// Composite checksums of multipart uploads are skipped because they are not a checksum of the body.
+    pub fn checksum(&self) -> Option<(ChecksumAlgorithm, &str)> {
+        if self
+            .headers()
+            .get(X_AMZ_CHECKSUM_TYPE)
+            .is_some_and(|value| value == "COMPOSITE")
+        {
+            return None;
+        }
+        ChecksumAlgorithm::ALL.into_iter().find_map(|algorithm| {
+            let checksum = self.headers().get(algorithm.header_name())?.to_str().ok()?;
+            // Composite checksums end with the number of parts
+            (!checksum.contains('-')).then_some((algorithm, checksum))
+        })
+    }
+    /// The body of the object.
+    ///
+    /// If [Self::checksum] is set the stream ends with [S3Error::ChecksumMismatch] if the body does not match it.
+    pub fn bytes_stream(self) -> impl Stream<Item = Result<Bytes, S3Error>> {
+        let checksum = self
+            .checksum()
+            .map(|(algorithm, checksum)| (algorithm, checksum.to_owned()));
+        let stream = self.0.bytes_stream();
+        match checksum {
+            Some((algorithm, checksum)) => {
+                Either::Left(ChecksumStream::new(stream, algorithm, checksum))
+            }
+            None => Either::Right(stream.map_err(S3Error::from)),
+        }
+    }
+    /// Reads the body with [Self::bytes_stream]
+    pub async fn bytes(self) -> Result<Bytes, S3Error> {
+        let content_length = self.content_length().ok().flatten().unwrap_or_default();
+        let mut stream = std::pin::pin!(self.bytes_stream());
+        let mut content = BytesMut::with_capacity(content_length as usize);
+        while let Some(chunk) = stream.try_next().await? {
+            content.extend_from_slice(&chunk);
+        }
+        Ok(content.freeze())
+    }
 }
 #[derive(Debug, Clone, Default)]
 pub struct GetObject<'request> {
     pub key: &'request str,
     pub accept: Option<HeaderValue>,
     pub ranged: Option<Ranged>,
+    /// Requests the checksum of the object. See [GetObjectResponse::bytes_stream]
+    pub checksum_mode: bool,
 }
 impl CommandType for GetObject<'_> {
+    fn name(&self) -> &'static str {
+        "GetObject"
+    }
     fn http_method(&self) -> http::Method {
         Method::GET
     }
@@ -90,12 +143,26 @@ impl CommandType for GetObject<'_> {
         if let Some(accept) = &self.accept {
             base.insert(ACCEPT, accept.clone());
         }
+        if self.checksum_mode {
+            base.insert(X_AMZ_CHECKSUM_MODE, HeaderValue::from_static("ENABLED"));
+        }
         Ok(())
     }
 }
 impl BucketCommandType for GetObject<'_> {}
 #[cfg(test)]
 mod tests {
+    use tux_io_s3_types::checksum::ChecksumAlgorithm;
+
+    use crate::{
+        S3Error,
+        command::{
+            S3CommandBody,
+            get::{GetObject, GetObjectResponse},
+            put::PutObject,
+        },
+        test::{IN_MEMORY_BUCKET, create_in_memory_bucket_client, init_test_logger},
+    };
 
     #[test]
     fn url_test() {
@@ -110,6 +177,59 @@ mod tests {
         command.update_url(&mut url).unwrap();
         assert_eq!(url.as_str(), "https://example.com/bucket1/test.txt");
     }
+    #[tokio::test]
+    async fn checked_download() -> anyhow::Result<()> {
+        init_test_logger();
+        let (s3, client) = create_in_memory_bucket_client();
+        client
+            .execute_command(PutObject {
+                key: "test.txt",
+                content: S3CommandBody::from("Hello World"),
+                checksum_algorithm: Some(ChecksumAlgorithm::Crc32),
+                ..Default::default()
+            })
+            .await?;
+        let get = |checksum_mode| GetObject {
+            key: "test.txt",
+            checksum_mode,
+            ..Default::default()
+        };
+
+        let response = GetObjectResponse(client.execute_command(get(true)).await?);
+        assert_eq!(
+            response.checksum(),
+            Some((ChecksumAlgorithm::Crc32, "ShexVg=="))
+        );
+        assert_eq!(response.bytes().await?, "Hello World");
+
+        let response = GetObjectResponse(client.execute_command(get(false)).await?);
+        assert_eq!(response.checksum(), None);
+
+        s3.set_object_checksum(
+            IN_MEMORY_BUCKET,
+            "test.txt",
+            ChecksumAlgorithm::Crc32,
+            "AAAAAA==",
+        );
+        let response = GetObjectResponse(client.execute_command(get(true)).await?);
+        let Err(S3Error::ChecksumMismatch(mismatch)) = response.bytes().await else {
+            panic!("Expected a checksum mismatch");
+        };
+        assert_eq!(mismatch.expected, "AAAAAA==");
+        assert_eq!(mismatch.calculated, "ShexVg==");
+
+        // Composite checksums of multipart uploads are not checked
+        s3.set_object_checksum(
+            IN_MEMORY_BUCKET,
+            "test.txt",
+            ChecksumAlgorithm::Crc32,
+            "AAAAAA==-2",
+        );
+        let response = GetObjectResponse(client.execute_command(get(true)).await?);
+        assert_eq!(response.checksum(), None);
+        assert_eq!(response.bytes().await?, "Hello World");
+        Ok(())
+    }
     #[cfg(feature = "client-testing")]
     mod client_tests {
         use crate::{
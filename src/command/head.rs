@@ -13,6 +13,9 @@ pub struct HeadObject<'request> {
 }
 impl BucketCommandType for HeadObject<'_> {}
 impl CommandType for HeadObject<'_> {
+    fn name(&self) -> &'static str {
+        "HeadObject"
+    }
     fn http_method(&self) -> http::Method {
         Method::HEAD
     }
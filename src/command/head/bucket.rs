@@ -6,6 +6,9 @@ use crate::command::{BucketCommandType, CommandType};
 pub struct HeadBucket;
 
 impl CommandType for HeadBucket {
+    fn name(&self) -> &'static str {
+        "HeadBucket"
+    }
     fn http_method(&self) -> http::Method {
         Method::HEAD
     }
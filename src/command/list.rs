@@ -1,15 +1,17 @@
 use std::borrow::Cow;
 
 use http::Method;
-use tux_io_s3_types::list::ListType;
+use tux_io_s3_types::list::{EncodingType, ListType};
 use url::Url;
 pub mod buckets;
 mod extensions;
+mod v1;
 use crate::{
     S3Error,
     command::{BucketCommandType, CommandType},
 };
 pub use extensions::*;
+pub use v1::*;
 /// List Objects V2 Command
 ///
 /// [AWS Docs](https://docs.aws.amazon.com/AmazonS3/latest/API/API_ListObjectsV2.html)
@@ -23,8 +25,11 @@ pub struct ListObjectsV2<'request, E: ListExtension = ()> {
     /// With a delimiter will have results with Common Prefixes
     pub delimiter: Option<Cow<'request, str>>,
     pub max_keys: Option<usize>,
-    pub start_after: Option<usize>,
+    /// Start listing after this key
+    pub start_after: Option<Cow<'request, str>>,
     pub fetch_owner: Option<bool>,
+    /// Encodes keys in the response. Decoded by [crate::client::BucketClient::list_objects_v2]
+    pub encoding_type: Option<EncodingType>,
     pub extension: E,
 }
 impl<'request, E> Default for ListObjectsV2<'request, E>
@@ -39,6 +44,7 @@ where
             max_keys: None,
             start_after: None,
             fetch_owner: None,
+            encoding_type: None,
             extension: E::default(),
         }
     }
@@ -65,6 +71,21 @@ impl<'request, E: ListExtension> ListObjectsV2<'request, E> {
         self.continuation_token = Some(continuation_token.into());
         self
     }
+    pub fn with_start_after<K>(mut self, start_after: K) -> Self
+    where
+        K: Into<Cow<'request, str>>,
+    {
+        self.start_after = Some(start_after.into());
+        self
+    }
+    pub fn with_max_keys(mut self, max_keys: usize) -> Self {
+        self.max_keys = Some(max_keys);
+        self
+    }
+    pub fn with_encoding_type(mut self, encoding_type: EncodingType) -> Self {
+        self.encoding_type = Some(encoding_type);
+        self
+    }
     pub fn with_extension<NE>(self, extension: NE) -> ListObjectsV2<'request, NE>
     where
         NE: ListExtension,
@@ -76,6 +97,7 @@ impl<'request, E: ListExtension> ListObjectsV2<'request, E> {
             max_keys: self.max_keys,
             start_after: self.start_after,
             fetch_owner: self.fetch_owner,
+            encoding_type: self.encoding_type,
             extension,
         }
     }
@@ -105,12 +127,16 @@ impl<E: ListExtension> CommandType for ListObjectsV2<'_, E> {
         }
         if let Some(start_after) = &self.start_after {
             url.query_pairs_mut()
-                .append_pair("start-after", &start_after.to_string());
+                .append_pair("start-after", start_after);
         }
         if let Some(fetch_owner) = &self.fetch_owner {
             url.query_pairs_mut()
                 .append_pair("fetch-owner", &fetch_owner.to_string());
         }
+        if let Some(encoding_type) = &self.encoding_type {
+            url.query_pairs_mut()
+                .append_pair("encoding-type", encoding_type.as_ref());
+        }
         self.extension.update_url(url)?;
         Ok(())
     }
@@ -119,7 +145,15 @@ impl<E: ListExtension> BucketCommandType for ListObjectsV2<'_, E> {}
 
 #[cfg(test)]
 mod tests {
-    use crate::test::init_test_logger;
+    use futures::TryStreamExt;
+
+    use crate::{
+        client::{ListObjectsVersion, http_client::InMemoryS3},
+        test::{
+            IN_MEMORY_BUCKET, create_in_memory_bucket_client, in_memory_client_builder,
+            init_test_logger,
+        },
+    };
 
     use super::*;
     #[test]
@@ -130,27 +164,46 @@ mod tests {
             continuation_token: Some(Cow::Borrowed("token")),
             delimiter: Some(Cow::Borrowed("/")),
             max_keys: Some(100),
-            start_after: Some(50),
+            start_after: Some(Cow::Borrowed("test/a.txt")),
             fetch_owner: Some(true),
             ..Default::default()
         };
         command.update_url(&mut url).unwrap();
         assert_eq!(
             url.as_str(),
-            "https://example.com/bucket1/?list-type=2&prefix=test%2F&delimiter=%2F&continuation-token=token&max-keys=100&start-after=50&fetch-owner=true"
+            "https://example.com/bucket1/?list-type=2&prefix=test%2F&delimiter=%2F&continuation-token=token&max-keys=100&start-after=test%2Fa.txt&fetch-owner=true"
         );
     }
     #[tokio::test]
     async fn request_check() -> anyhow::Result<()> {
         init_test_logger();
-        let client = crate::test::create_test_bucket_client();
+        let (s3, client) = create_in_memory_bucket_client();
+        for key in [
+            "a.txt",
+            "rfl/one.txt",
+            "rfl/two.txt",
+            "rfl/nested/three.txt",
+            "z/four.txt",
+        ] {
+            s3.put_object(IN_MEMORY_BUCKET, key, "content");
+        }
         let result = client
             .list_objects_v2(ListObjectsV2 {
                 prefix: Cow::Borrowed("rfl/"),
                 ..Default::default()
             })
             .await?;
-        println!("{:#?}", result);
+        let keys: Vec<_> = result
+            .contents
+            .iter()
+            .flatten()
+            .map(|object| object.key.as_str())
+            .collect();
+        assert_eq!(
+            keys,
+            vec!["rfl/nested/three.txt", "rfl/one.txt", "rfl/two.txt"]
+        );
+        assert!(!result.is_truncated);
 
         let result = client
             .list_objects_v2(ListObjectsV2 {
@@ -158,7 +211,210 @@ mod tests {
                 ..Default::default()
             })
             .await?;
-        println!("{:#?}", result);
+        let keys: Vec<_> = result
+            .contents
+            .iter()
+            .flatten()
+            .map(|object| object.key.as_str())
+            .collect();
+        assert_eq!(keys, vec!["a.txt"]);
+        assert_eq!(
+            result.common_prefixes.as_deref(),
+            Some(&vec!["rfl/".to_string(), "z/".to_string()])
+        );
+        Ok(())
+    }
+    #[tokio::test]
+    async fn continuation() -> anyhow::Result<()> {
+        init_test_logger();
+        let (s3, client) = create_in_memory_bucket_client();
+        for index in 0..5 {
+            s3.put_object(IN_MEMORY_BUCKET, format!("key-{index}"), "content");
+        }
+        let mut keys = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let mut command = ListObjectsV2 {
+                max_keys: Some(2),
+                ..Default::default()
+            };
+            if let Some(token) = &token {
+                command = command.with_continuation_token(token.clone());
+            }
+            let result = client.list_objects_v2(command).await?;
+            keys.extend(
+                result
+                    .contents
+                    .into_iter()
+                    .flatten()
+                    .map(|object| object.key),
+            );
+            match result.next_continuation_token {
+                Some(next) if result.is_truncated => token = Some(next),
+                _ => break,
+            }
+        }
+        assert_eq!(keys, s3.object_keys(IN_MEMORY_BUCKET));
+        Ok(())
+    }
+    #[tokio::test]
+    async fn stream() -> anyhow::Result<()> {
+        init_test_logger();
+        let (s3, client) = create_in_memory_bucket_client();
+        for key in [
+            "a.txt",
+            "b/one.txt",
+            "b/two.txt",
+            "c.txt",
+            "d/three.txt",
+            "e.txt",
+        ] {
+            s3.put_object(IN_MEMORY_BUCKET, key, "content");
+        }
+        let command = ListObjectsV2::default()
+            .with_delimiter("/")
+            .with_max_keys(2);
+        let entries: Vec<_> = client
+            .list_objects_v2_stream(command.clone(), None)
+            .map_ok(|entry| entry.key().to_owned())
+            .try_collect()
+            .await?;
+        assert_eq!(entries, vec!["a.txt", "b/", "c.txt", "d/", "e.txt"]);
+
+        let pages: Vec<_> = client
+            .list_objects_v2_pages(command, None)
+            .try_collect()
+            .await?;
+        assert_eq!(pages.len(), 3);
+        assert!(pages[0].is_truncated);
+
+        let capped: Vec<_> = client
+            .list_objects_v2_stream(ListObjectsV2::default().with_start_after("a.txt"), Some(3))
+            .map_ok(|entry| entry.key().to_owned())
+            .try_collect()
+            .await?;
+        assert_eq!(capped, vec!["b/one.txt", "b/two.txt", "c.txt"]);
+        Ok(())
+    }
+    #[tokio::test]
+    async fn v1_markers() -> anyhow::Result<()> {
+        init_test_logger();
+        let (s3, client) = create_in_memory_bucket_client();
+        for key in ["a.txt", "b/one.txt", "b/two.txt", "c.txt", "d.txt"] {
+            s3.put_object(IN_MEMORY_BUCKET, key, "content");
+        }
+        let page = client
+            .list_objects_v1(ListObjectsV1::default().with_max_keys(2))
+            .await?;
+        assert!(page.is_truncated);
+        assert_eq!(page.next_marker, None);
+        assert_eq!(page.next_page_marker(), Some("b/one.txt"));
+
+        let keys: Vec<_> = client
+            .list_objects_v1_stream(ListObjectsV1::default().with_max_keys(2), None)
+            .map_ok(|entry| entry.key().to_owned())
+            .try_collect()
+            .await?;
+        assert_eq!(keys, s3.object_keys(IN_MEMORY_BUCKET));
+
+        let entries: Vec<_> = client
+            .list_objects_v1_stream(
+                ListObjectsV1::default()
+                    .with_delimiter("/")
+                    .with_max_keys(1),
+                None,
+            )
+            .map_ok(|entry| entry.key().to_owned())
+            .try_collect()
+            .await?;
+        assert_eq!(entries, vec!["a.txt", "b/", "c.txt", "d.txt"]);
+        Ok(())
+    }
+    #[tokio::test]
+    async fn auto_version() -> anyhow::Result<()> {
+        init_test_logger();
+        for (s3, expected) in [
+            (InMemoryS3::new(), ListObjectsVersion::V2),
+            (
+                InMemoryS3::new().without_list_objects_v2(),
+                ListObjectsVersion::V1,
+            ),
+        ] {
+            s3.create_bucket(IN_MEMORY_BUCKET);
+            for key in ["a.txt", "b.txt", "c.txt"] {
+                s3.put_object(IN_MEMORY_BUCKET, key, "content");
+            }
+            let client = in_memory_client_builder(&s3)
+                .with_list_objects_version(ListObjectsVersion::Auto)
+                .bucket_client(IN_MEMORY_BUCKET)?;
+            let keys: Vec<_> = client
+                .list_objects_stream(ListObjectsV2::default().with_start_after("a.txt"), None)
+                .map_ok(|entry| entry.key().to_owned())
+                .try_collect()
+                .await?;
+            assert_eq!(keys, vec!["b.txt", "c.txt"]);
+            assert_eq!(client.list_objects_version().await?, expected);
+
+            let requests = s3.request_count();
+            client.list_objects_version().await?;
+            assert_eq!(s3.request_count(), requests, "The probe is only sent once");
+        }
+        Ok(())
+    }
+    #[tokio::test]
+    async fn continuation_token_with_v1() -> anyhow::Result<()> {
+        init_test_logger();
+        let s3 = InMemoryS3::new()
+            .with_bucket(IN_MEMORY_BUCKET)
+            .without_list_objects_v2();
+        let client = in_memory_client_builder(&s3)
+            .with_list_objects_version(ListObjectsVersion::V1)
+            .bucket_client(IN_MEMORY_BUCKET)?;
+        let result: Result<Vec<_>, _> = client
+            .list_objects_stream(
+                ListObjectsV2::default().with_continuation_token("token"),
+                None,
+            )
+            .try_collect()
+            .await;
+        assert!(matches!(result, Err(S3Error::ContinuationTokenWithV1)));
+        assert_eq!(s3.request_count(), 0);
+        Ok(())
+    }
+    #[tokio::test]
+    async fn url_encoding() -> anyhow::Result<()> {
+        init_test_logger();
+        let (s3, client) = create_in_memory_bucket_client();
+        for key in [
+            "dir one/a\u{1}b.txt",
+            "dir one/c+d.txt",
+            "dir one/nested/e.txt",
+        ] {
+            s3.put_object(IN_MEMORY_BUCKET, key, "content");
+        }
+        let command = ListObjectsV2::default()
+            .with_prefix("dir one/")
+            .with_delimiter("/")
+            .with_encoding_type(EncodingType::Url);
+        let page = client.list_objects_v2(command.clone()).await?;
+        assert_eq!(page.encoding_type, None);
+        assert_eq!(page.prefix.as_deref(), Some("dir one/"));
+        let entries: Vec<_> = page
+            .into_entries()
+            .into_iter()
+            .map(|entry| entry.key().to_owned())
+            .collect();
+        assert_eq!(
+            entries,
+            vec!["dir one/a\u{1}b.txt", "dir one/c+d.txt", "dir one/nested/"]
+        );
+
+        let v1: Vec<_> = client
+            .list_objects_v1_stream(ListObjectsV1::from(command.with_max_keys(1)), None)
+            .map_ok(|entry| entry.key().to_owned())
+            .try_collect()
+            .await?;
+        assert_eq!(v1, entries);
         Ok(())
     }
 }
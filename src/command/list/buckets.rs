@@ -1,18 +1,52 @@
-use tux_io_s3_types::region::{RegionType, S3Region};
+use std::borrow::Cow;
+
 use url::Url;
 
 use crate::{
     S3Error,
     command::{AccountCommandType, CommandType},
 };
-
+/// List Buckets Command
+///
+/// [AWS Docs](https://docs.aws.amazon.com/AmazonS3/latest/API/API_ListBuckets.html)
+#[derive(Debug, Clone, Default)]
 pub struct ListBuckets<'request> {
-    pub region: &'request S3Region,
-    pub continuation_token: Option<String>,
+    pub prefix: Option<Cow<'request, str>>,
+    /// Only list buckets in this region
+    pub bucket_region: Option<Cow<'request, str>>,
+    pub continuation_token: Option<Cow<'request, str>>,
+    /// Buckets per page. Between 1 and 10000
     pub max_buckets: Option<usize>,
-    pub prefix: Option<String>,
 }
-
+/// Parameters of [crate::client::S3Client::list_buckets]
+pub type ListBucketsRequest<'request> = ListBuckets<'request>;
+impl<'request> ListBuckets<'request> {
+    pub fn with_prefix<P>(mut self, prefix: P) -> Self
+    where
+        P: Into<Cow<'request, str>>,
+    {
+        self.prefix = Some(prefix.into());
+        self
+    }
+    pub fn with_bucket_region<R>(mut self, bucket_region: R) -> Self
+    where
+        R: Into<Cow<'request, str>>,
+    {
+        self.bucket_region = Some(bucket_region.into());
+        self
+    }
+    pub fn with_continuation_token<T>(mut self, continuation_token: T) -> Self
+    where
+        T: Into<Cow<'request, str>>,
+    {
+        self.continuation_token = Some(continuation_token.into());
+        self
+    }
+    pub fn with_max_buckets(mut self, max_buckets: usize) -> Self {
+        self.max_buckets = Some(max_buckets);
+        self
+    }
+}
 impl CommandType for ListBuckets<'_> {
     fn name(&self) -> &'static str {
         "ListBuckets"
@@ -22,8 +56,10 @@ impl CommandType for ListBuckets<'_> {
         http::Method::GET
     }
     fn update_url(&self, url: &mut Url) -> Result<(), S3Error> {
-        url.query_pairs_mut()
-            .append_pair("bucket-region", self.region.name());
+        if let Some(bucket_region) = &self.bucket_region {
+            url.query_pairs_mut()
+                .append_pair("bucket-region", bucket_region);
+        }
         if let Some(prefix) = &self.prefix {
             url.query_pairs_mut().append_pair("prefix", prefix);
         }
@@ -42,6 +78,122 @@ impl AccountCommandType for ListBuckets<'_> {}
 
 #[cfg(test)]
 mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use futures::TryStreamExt;
+    use tux_io_s3_types::region::OfficialRegion;
+    use url::Url;
+
+    use super::*;
+    use crate::client::{
+        EndpointVariants, Interceptor, InterceptorContext, S3Client, S3ClientBuilder,
+        http_client::InMemoryS3,
+    };
+
+    fn client(s3: InMemoryS3) -> anyhow::Result<S3Client<InMemoryS3>> {
+        let region: tux_io_s3_types::region::CustomRegion = "http://localhost:9000".parse()?;
+        let client = S3ClientBuilder::default()
+            .with_region(region)
+            .with_credentials(Default::default())
+            .with_transport(s3)
+            .build()?;
+        Ok(client)
+    }
+
+    #[tokio::test]
+    async fn in_memory() -> anyhow::Result<()> {
+        let s3 = InMemoryS3::new()
+            .with_bucket("bucket-a")
+            .with_bucket("bucket-b");
+        let client = client(s3)?;
+        let response = client.list_my_buckets().await?;
+        let names: Vec<_> = response
+            .buckets
+            .buckets
+            .iter()
+            .map(|bucket| bucket.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["bucket-a", "bucket-b"]);
+        Ok(())
+    }
+    /// Records the url of every request
+    #[derive(Debug, Default)]
+    struct RequestUrls(Mutex<Vec<Url>>);
+    impl Interceptor for RequestUrls {
+        fn after_signing(
+            &self,
+            _context: &InterceptorContext,
+            request: &mut reqwest::Request,
+        ) -> Result<Option<reqwest::Response>, S3Error> {
+            self.0.lock().unwrap().push(request.url().clone());
+            Ok(None)
+        }
+    }
+    #[tokio::test]
+    async fn accelerate() -> anyhow::Result<()> {
+        let urls = Arc::new(RequestUrls::default());
+        let client = S3ClientBuilder::default()
+            .with_region(OfficialRegion::EuWest1)
+            .with_endpoint_variants(
+                EndpointVariants::default()
+                    .with_accelerate(true)
+                    .with_dual_stack(true),
+            )
+            .with_credentials(Default::default())
+            .with_interceptor(urls.clone())
+            .with_transport(InMemoryS3::new().with_bucket("bucket"))
+            .build()?;
+        assert_eq!(client.host()?, "s3.dualstack.eu-west-1.amazonaws.com");
+        let response = client.list_my_buckets().await?;
+        assert_eq!(response.buckets.buckets[0].name, "bucket");
+        assert_eq!(
+            urls.0.lock().unwrap()[0].host_str(),
+            Some("s3.dualstack.eu-west-1.amazonaws.com")
+        );
+        let bucket = client.open_bucket("bucket");
+        assert_eq!(
+            bucket.host()?,
+            "bucket.s3-accelerate.dualstack.amazonaws.com"
+        );
+        Ok(())
+    }
+    #[tokio::test]
+    async fn pages() -> anyhow::Result<()> {
+        let s3 = InMemoryS3::new();
+        for name in ["logs-a", "logs-b", "logs-c", "data-a", "data-b"] {
+            s3.create_bucket(name);
+        }
+        s3.set_bucket_region("data-b", "eu-west-1");
+        let client = client(s3)?;
+        let page = client
+            .list_buckets(ListBuckets::default().with_max_buckets(2))
+            .await?;
+        assert_eq!(page.buckets.buckets.len(), 2);
+        assert_eq!(page.continuation_token.as_deref(), Some("data-b"));
+
+        let names: Vec<_> = client
+            .list_buckets_stream(
+                ListBuckets::default()
+                    .with_prefix("logs-")
+                    .with_max_buckets(2),
+            )
+            .map_ok(|bucket| bucket.name)
+            .try_collect()
+            .await?;
+        assert_eq!(names, vec!["logs-a", "logs-b", "logs-c"]);
+
+        let in_region: Vec<_> = client
+            .list_buckets_stream(ListBuckets::default().with_bucket_region("eu-west-1"))
+            .try_collect()
+            .await?;
+        assert_eq!(
+            in_region.len(),
+            5,
+            "Buckets without a region match every region"
+        );
+        assert_eq!(in_region[1].bucket_region.as_deref(), Some("eu-west-1"));
+        Ok(())
+    }
     #[cfg(feature = "client-testing")]
     mod client_testing {
         use crate::test::init_test_logger;
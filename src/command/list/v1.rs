@@ -0,0 +1,120 @@
+use std::borrow::Cow;
+
+use http::Method;
+use tux_io_s3_types::list::EncodingType;
+use url::Url;
+
+use crate::{
+    S3Error,
+    command::{BucketCommandType, CommandType, list::ListObjectsV2},
+};
+/// List Objects (V1) Command
+///
+/// Paginates with markers instead of continuation tokens. Only use it for endpoints that do not support [ListObjectsV2].
+///
+/// [AWS Docs](https://docs.aws.amazon.com/AmazonS3/latest/API/API_ListObjects.html)
+#[derive(Debug, Clone, Default)]
+pub struct ListObjectsV1<'request> {
+    pub prefix: Cow<'request, str>,
+    /// Having a delimiter in the request changes how the request works.
+    /// No Delimiter will list everything
+    ///
+    /// With a delimiter will have results with Common Prefixes
+    pub delimiter: Option<Cow<'request, str>>,
+    /// Start listing after this key
+    pub marker: Option<Cow<'request, str>>,
+    pub max_keys: Option<usize>,
+    /// Encodes keys in the response. Decoded by [crate::client::BucketClient::list_objects_v1]
+    pub encoding_type: Option<EncodingType>,
+}
+impl<'request> ListObjectsV1<'request> {
+    pub fn with_delimiter<D>(mut self, delimiter: D) -> Self
+    where
+        D: Into<Cow<'request, str>>,
+    {
+        self.delimiter = Some(delimiter.into());
+        self
+    }
+    pub fn with_prefix<P>(mut self, prefix: P) -> Self
+    where
+        P: Into<Cow<'request, str>>,
+    {
+        self.prefix = prefix.into();
+        self
+    }
+    pub fn with_marker<M>(mut self, marker: M) -> Self
+    where
+        M: Into<Cow<'request, str>>,
+    {
+        self.marker = Some(marker.into());
+        self
+    }
+    pub fn with_max_keys(mut self, max_keys: usize) -> Self {
+        self.max_keys = Some(max_keys);
+        self
+    }
+    pub fn with_encoding_type(mut self, encoding_type: EncodingType) -> Self {
+        self.encoding_type = Some(encoding_type);
+        self
+    }
+}
+/// `start-after` becomes the marker. The continuation token is dropped, it is only understood by [ListObjectsV2]
+impl<'request> From<ListObjectsV2<'request>> for ListObjectsV1<'request> {
+    fn from(value: ListObjectsV2<'request>) -> Self {
+        Self {
+            prefix: value.prefix,
+            delimiter: value.delimiter,
+            marker: value.start_after,
+            max_keys: value.max_keys,
+            encoding_type: value.encoding_type,
+        }
+    }
+}
+impl CommandType for ListObjectsV1<'_> {
+    fn name(&self) -> &'static str {
+        "ListObjects"
+    }
+    fn http_method(&self) -> Method {
+        Method::GET
+    }
+
+    fn update_url(&self, url: &mut Url) -> Result<(), S3Error> {
+        url.query_pairs_mut().append_pair("prefix", &self.prefix);
+        if let Some(delimiter) = &self.delimiter {
+            url.query_pairs_mut().append_pair("delimiter", delimiter);
+        }
+        if let Some(marker) = &self.marker {
+            url.query_pairs_mut().append_pair("marker", marker);
+        }
+        if let Some(max_keys) = &self.max_keys {
+            url.query_pairs_mut()
+                .append_pair("max-keys", &max_keys.to_string());
+        }
+        if let Some(encoding_type) = &self.encoding_type {
+            url.query_pairs_mut()
+                .append_pair("encoding-type", encoding_type.as_ref());
+        }
+        Ok(())
+    }
+}
+impl BucketCommandType for ListObjectsV1<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn url_test() {
+        let mut url = url::Url::parse("https://example.com/bucket1/").unwrap();
+        let command = ListObjectsV1::default()
+            .with_prefix("test/")
+            .with_delimiter("/")
+            .with_marker("test/a.txt")
+            .with_max_keys(100)
+            .with_encoding_type(EncodingType::Url);
+        command.update_url(&mut url).unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://example.com/bucket1/?prefix=test%2F&delimiter=%2F&marker=test%2Fa.txt&max-keys=100&encoding-type=url"
+        );
+    }
+}
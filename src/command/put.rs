@@ -2,7 +2,10 @@ use std::{borrow::Cow, str::FromStr};
 
 use ahash::AHashMap;
 use http::{HeaderMap, HeaderName, HeaderValue, Method};
-use tux_io_s3_types::tag::{AnyTaggingSet, TAGGING_HEADER};
+use tux_io_s3_types::{
+    checksum::ChecksumAlgorithm,
+    tag::{AnyTaggingSet, TAGGING_HEADER},
+};
 mod actions;
 use url::Url;
 mod tagging;
@@ -95,11 +98,21 @@ pub struct PutObject<'request> {
     pub tags: Option<AnyTaggingSet<'request>>,
     pub content: S3CommandBody,
     pub headers: PutHeaders,
+    /// Checksum S3 verifies the content with. See [S3CommandBody::with_checksum]
+    pub checksum_algorithm: Option<ChecksumAlgorithm>,
+    /// Sends a `Content-MD5` of the content. Streams can not be hashed before they are sent.
+    pub content_md5: bool,
 }
 impl CommandType for PutObject<'_> {
+    fn name(&self) -> &'static str {
+        "PutObject"
+    }
     fn http_method(&self) -> Method {
         Method::PUT
     }
+    fn requires_content_md5(&self) -> bool {
+        self.content_md5
+    }
     fn update_url(&self, url: &mut Url) -> Result<(), S3Error> {
         url.append_path(self.key.as_ref())?;
         Ok(())
@@ -121,13 +134,207 @@ impl CommandType for PutObject<'_> {
         Ok(())
     }
     fn into_body(self) -> Result<super::S3CommandBody, S3Error> {
-        Ok(self.content)
+        Ok(match self.checksum_algorithm {
+            Some(algorithm) => self.content.with_checksum(algorithm),
+            None => self.content,
+        })
     }
 }
 
 impl BucketCommandType for PutObject<'_> {}
 #[cfg(test)]
 mod test {
+    use bytes::Bytes;
+    use http::StatusCode;
+    use tux_io_s3_types::{
+        checksum::ChecksumAlgorithm,
+        headers::CONTENT_MD5,
+        tag::{AnyTaggingSet, BorrowedTag, BorrowedTaggingSet, TagType},
+    };
+
+    use crate::{
+        S3Error,
+        client::http_client::HttpClient,
+        command::{
+            S3CommandBody,
+            put::{PutHeaders, PutObject},
+        },
+        test::{
+            IN_MEMORY_BUCKET, create_in_memory_bucket_client, generate_test_content,
+            init_test_logger,
+        },
+        utils::checksum::checksum,
+    };
+    #[tokio::test]
+    async fn stream_upload() -> anyhow::Result<()> {
+        init_test_logger();
+        let (s3, client) = create_in_memory_bucket_client();
+        let content = generate_test_content(300 * 1024);
+        let chunks: Vec<Result<Bytes, std::io::Error>> = content
+            .chunks(16 * 1024)
+            .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+            .collect();
+        let borrowed_tags = BorrowedTaggingSet::new(vec![
+            BorrowedTag::from(("key1", "value1")),
+            BorrowedTag::from(("key2", "value2")),
+        ]);
+        let put_object = PutObject {
+            key: "test-file.txt",
+            tags: Some(AnyTaggingSet::Borrowed(borrowed_tags)),
+            content: S3CommandBody::wrap_stream(futures::stream::iter(chunks), content.len()),
+            headers: PutHeaders {
+                content_type: "text/plain".into(),
+                ..Default::default()
+            },
+            checksum_algorithm: None,
+            content_md5: false,
+        };
+        let response = client.execute_command(put_object).await?;
+        assert!(
+            response.status().is_success(),
+            "Failed to upload file: {}",
+            response.text().await?
+        );
+
+        let object = s3
+            .get_object(IN_MEMORY_BUCKET, "test-file.txt")
+            .expect("Object was not stored");
+        assert_eq!(object.content, content);
+        assert_eq!(object.content_type, "text/plain");
+        assert_eq!(
+            object
+                .tags
+                .iter()
+                .map(|tag| (tag.key(), tag.value()))
+                .collect::<Vec<_>>(),
+            vec![("key1", "value1"), ("key2", "value2")]
+        );
+        Ok(())
+    }
+    #[tokio::test]
+    async fn checksums() -> anyhow::Result<()> {
+        init_test_logger();
+        let (s3, client) = create_in_memory_bucket_client();
+        let content = generate_test_content(300 * 1024);
+        for algorithm in ChecksumAlgorithm::ALL {
+            let key = format!("fixed-{algorithm}.bin");
+            let put = PutObject {
+                key: &key,
+                content: S3CommandBody::from(content.clone()),
+                checksum_algorithm: Some(algorithm),
+                ..Default::default()
+            };
+            let response = client.execute_command(put).await?;
+            assert_eq!(response.status(), StatusCode::OK, "{algorithm}");
+            let expected = checksum(algorithm, &content);
+            assert_eq!(response.headers()[algorithm.header_name()], expected);
+            let object = s3.get_object(IN_MEMORY_BUCKET, &key).expect("Object");
+            assert_eq!(object.checksum, Some((algorithm, expected)));
+        }
+        // Streams send the checksum as a trailer
+        let chunks: Vec<Result<Bytes, std::io::Error>> = content
+            .chunks(16 * 1024)
+            .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+            .collect();
+        let put = PutObject {
+            key: "stream.bin",
+            content: S3CommandBody::wrap_stream(futures::stream::iter(chunks), content.len()),
+            checksum_algorithm: Some(ChecksumAlgorithm::Crc32c),
+            ..Default::default()
+        };
+        let response = client.execute_command(put).await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        let object = s3
+            .get_object(IN_MEMORY_BUCKET, "stream.bin")
+            .expect("Object");
+        assert_eq!(object.content, content);
+        assert_eq!(
+            object.checksum,
+            Some((
+                ChecksumAlgorithm::Crc32c,
+                checksum(ChecksumAlgorithm::Crc32c, &content)
+            ))
+        );
+
+        let request = s3
+            .put(
+                format!("http://localhost:9000/{IN_MEMORY_BUCKET}/wrong.bin")
+                    .parse::<url::Url>()?,
+            )
+            .header(ChecksumAlgorithm::Crc32.header_name(), "AAAAAA==")
+            .body("Hello World")
+            .build()?;
+        let response = s3.execute(request).await?;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert!(s3.get_object(IN_MEMORY_BUCKET, "wrong.bin").is_none());
+        Ok(())
+    }
+    #[tokio::test]
+    async fn content_md5() -> anyhow::Result<()> {
+        init_test_logger();
+        let (s3, client) = create_in_memory_bucket_client();
+        let content = generate_test_content(300 * 1024);
+        let response = client
+            .execute_command(PutObject {
+                key: "fixed.bin",
+                content: S3CommandBody::from(content.clone()),
+                content_md5: true,
+                ..Default::default()
+            })
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        let object = s3
+            .get_object(IN_MEMORY_BUCKET, "fixed.bin")
+            .expect("Object");
+        assert_eq!(object.content, content);
+
+        let chunks: Vec<Result<Bytes, std::io::Error>> = content
+            .chunks(16 * 1024)
+            .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+            .collect();
+        let result = client
+            .execute_command(PutObject {
+                key: "stream.bin",
+                content: S3CommandBody::wrap_stream(futures::stream::iter(chunks), content.len()),
+                content_md5: true,
+                ..Default::default()
+            })
+            .await;
+        assert!(matches!(result, Err(S3Error::ContentMd5ForStream)));
+
+        let request = s3
+            .put(
+                format!("http://localhost:9000/{IN_MEMORY_BUCKET}/wrong.bin")
+                    .parse::<url::Url>()?,
+            )
+            .header(CONTENT_MD5, "1B2M2Y8AsgTpgAcHCc4o/w==")
+            .body("Hello World")
+            .build()?;
+        let response = s3.execute(request).await?;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert!(s3.get_object(IN_MEMORY_BUCKET, "wrong.bin").is_none());
+        Ok(())
+    }
+    #[tokio::test]
+    async fn conflict() -> anyhow::Result<()> {
+        init_test_logger();
+        let (_, client) = create_in_memory_bucket_client();
+        let put = |if_none_match: Option<&'static str>| PutObject {
+            key: "conflict.txt",
+            content: S3CommandBody::from(Bytes::from_static(b"This is a test file content.")),
+            headers: PutHeaders {
+                if_none_match: if_none_match.map(Into::into),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let response = client.execute_command(put(None)).await?;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = client.execute_command(put(Some("*"))).await?;
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+        Ok(())
+    }
     #[cfg(feature = "client-testing")]
     mod client_testing {
 
@@ -161,6 +368,8 @@ mod test {
                     content_type: "text/plain".into(),
                     ..Default::default()
                 },
+                checksum_algorithm: None,
+                content_md5: false,
             };
 
             let response = client.execute_command(put_object).await?;
@@ -179,6 +388,8 @@ mod test {
                     if_none_match: Some("*".into()),
                     ..Default::default()
                 },
+                checksum_algorithm: None,
+                content_md5: false,
             };
             let response_conflict = client.execute_command(put_object_conflict).await?;
             let text = response_conflict.text().await?;
@@ -206,44 +417,8 @@ mod test {
                     content_type: "text/plain".into(),
                     ..Default::default()
                 },
-            };
-
-            let response = client.execute_command(put_object).await?;
-
-            assert!(
-                response.status().is_success(),
-                "Failed to upload file: {}",
-                response.text().await?
-            );
-
-            Ok(())
-        }
-
-        #[tokio::test]
-        async fn test_raw_file() -> anyhow::Result<()> {
-            let file = tokio::fs::File::open(
-                "/media/Other/PersonalProjects/RustProjects/rust-tests/test_file.txt",
-            )
-            .await?;
-            let file_size = file.metadata().await?.len();
-            let stream = tokio_util::io::ReaderStream::new(file);
-            let body = S3CommandBody::wrap_stream(stream, file_size as usize);
-            init_test_logger();
-            let client = create_test_bucket_client();
-            let path = "test-file.txt";
-
-            let borrowed_tags = BorrowedTaggingSet::new(vec![
-                BorrowedTag::from(("key1", "value1")),
-                BorrowedTag::from(("key2", "value2")),
-            ]);
-            let put_object = PutObject {
-                key: path,
-                tags: Some(AnyTaggingSet::Borrowed(borrowed_tags)),
-                content: body,
-                headers: PutHeaders {
-                    content_type: "text/plain".into(),
-                    ..Default::default()
-                },
+                checksum_algorithm: None,
+                content_md5: false,
             };
 
             let response = client.execute_command(put_object).await?;
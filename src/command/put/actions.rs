@@ -22,6 +22,9 @@ impl<'request> CopyObject<'request> {
 }
 
 impl CommandType for CopyObject<'_> {
+    fn name(&self) -> &'static str {
+        "CopyObject"
+    }
     fn http_method(&self) -> http::Method {
         Method::PUT
     }
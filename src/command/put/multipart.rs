@@ -1,7 +1,11 @@
 use std::borrow::Cow;
 
-use http::{HeaderMap, Method};
-use tux_io_s3_types::tag::{AnyTaggingSet, TAGGING_HEADER};
+use http::{HeaderMap, HeaderValue, Method};
+use tux_io_s3_types::{
+    checksum::ChecksumAlgorithm,
+    headers::X_AMZ_CHECKSUM_ALGORITHM,
+    tag::{AnyTaggingSet, TAGGING_HEADER},
+};
 use url::Url;
 
 use crate::{
@@ -14,8 +18,13 @@ pub struct CreateMultipartUpload<'request> {
     pub key: &'request str,
     pub tags: Option<AnyTaggingSet<'request>>,
     pub headers: PutHeaders,
+    /// Every part must be uploaded with a checksum of this algorithm. See [PutPart::checksum_algorithm]
+    pub checksum_algorithm: Option<ChecksumAlgorithm>,
 }
 impl CommandType for CreateMultipartUpload<'_> {
+    fn name(&self) -> &'static str {
+        "CreateMultipartUpload"
+    }
     fn http_method(&self) -> Method {
         Method::POST
     }
@@ -32,6 +41,12 @@ impl CommandType for CreateMultipartUpload<'_> {
         for (name, value) in &self.headers.metadata {
             base.insert(name.0.clone(), value.clone());
         }
+        if let Some(algorithm) = self.checksum_algorithm {
+            base.insert(
+                X_AMZ_CHECKSUM_ALGORITHM,
+                HeaderValue::from_static(algorithm.as_str()),
+            );
+        }
         Ok(())
     }
 }
@@ -43,11 +58,21 @@ pub struct PutPart<'request> {
     pub part_number: u32,
     pub upload_id: Cow<'request, str>,
     pub content: S3CommandBody,
+    /// The checksum of the part must be passed to [CompleteMultipartUpload] with [tux_io_s3_types::multi_part::Part::with_checksum]
+    pub checksum_algorithm: Option<ChecksumAlgorithm>,
+    /// Sends a `Content-MD5` of the content. Streams can not be hashed before they are sent.
+    pub content_md5: bool,
 }
 impl CommandType for PutPart<'_> {
+    fn name(&self) -> &'static str {
+        "UploadPart"
+    }
     fn http_method(&self) -> Method {
         Method::PUT
     }
+    fn requires_content_md5(&self) -> bool {
+        self.content_md5
+    }
     fn update_url(&self, url: &mut Url) -> Result<(), S3Error> {
         url.append_path(self.key.as_ref())?;
 
@@ -60,7 +85,10 @@ impl CommandType for PutPart<'_> {
         Ok(())
     }
     fn into_body(self) -> Result<S3CommandBody, S3Error> {
-        Ok(self.content)
+        Ok(match self.checksum_algorithm {
+            Some(algorithm) => self.content.with_checksum(algorithm),
+            None => self.content,
+        })
     }
 }
 
@@ -72,6 +100,9 @@ pub struct CompleteMultipartUpload<'request> {
     pub content: tux_io_s3_types::multi_part::CompleteMultipartUpload,
 }
 impl CommandType for CompleteMultipartUpload<'_> {
+    fn name(&self) -> &'static str {
+        "CompleteMultipartUpload"
+    }
     fn http_method(&self) -> Method {
         Method::POST
     }
@@ -97,6 +128,9 @@ pub struct AbortMultipartUpload<'request> {
     pub upload_id: Cow<'request, str>,
 }
 impl CommandType for AbortMultipartUpload<'_> {
+    fn name(&self) -> &'static str {
+        "AbortMultipartUpload"
+    }
     fn http_method(&self) -> Method {
         Method::DELETE
     }
@@ -111,101 +145,243 @@ impl CommandType for AbortMultipartUpload<'_> {
     }
 }
 impl BucketCommandType for AbortMultipartUpload<'_> {}
+/// Lists the parts that have been uploaded for a multipart upload
+///
+/// [AWS Docs](https://docs.aws.amazon.com/AmazonS3/latest/API/API_ListParts.html)
+pub struct ListParts<'request> {
+    pub key: &'request str,
+    pub upload_id: Cow<'request, str>,
+    pub max_parts: Option<u32>,
+    /// Only parts with a higher part number are listed
+    pub part_number_marker: Option<u32>,
+}
+impl CommandType for ListParts<'_> {
+    fn name(&self) -> &'static str {
+        "ListParts"
+    }
+    fn http_method(&self) -> Method {
+        Method::GET
+    }
+    fn update_url(&self, url: &mut Url) -> Result<(), S3Error> {
+        url.append_path(self.key.as_ref())?;
+        url.query_pairs_mut()
+            .append_pair("uploadId", &self.upload_id);
+        if let Some(max_parts) = self.max_parts {
+            url.query_pairs_mut()
+                .append_pair("max-parts", &max_parts.to_string());
+        }
+        if let Some(marker) = self.part_number_marker {
+            url.query_pairs_mut()
+                .append_pair("part-number-marker", &marker.to_string());
+        }
+        Ok(())
+    }
+}
+impl BucketCommandType for ListParts<'_> {}
 #[cfg(test)]
 mod tests {
+    use std::borrow::Cow;
 
-    #[cfg(feature = "client-testing")]
-    mod client_tests {
-        use std::borrow::Cow;
+    use futures::TryStreamExt;
+    use http::{StatusCode, header::ETAG};
+    use tux_io_s3_types::{
+        checksum::ChecksumAlgorithm,
+        multi_part::{InitiateMultipartUploadResult, Part},
+        tag::{AnyTaggingSet, BorrowedTag, BorrowedTaggingSet},
+    };
 
-        use futures::TryStreamExt;
-        use http::header::ETAG;
-        use tux_io_s3_types::{
-            multi_part::{InitiateMultipartUploadResult, Part},
-            tag::{AnyTaggingSet, BorrowedTag, BorrowedTaggingSet},
-        };
+    use crate::{
+        command::{
+            S3CommandBody,
+            put::{
+                AbortMultipartUpload, CompleteMultipartUpload, CreateMultipartUpload, PutHeaders,
+                PutPart,
+            },
+        },
+        test::{
+            IN_MEMORY_BUCKET, create_in_memory_bucket_client, generate_test_content,
+            init_test_logger,
+        },
+        utils::checksum::checksum,
+        utils::stream::MinimumSizedStream,
+    };
 
-        use crate::{
-            command::{
-                S3CommandBody,
-                put::{CreateMultipartUpload, PutHeaders, PutPart},
+    #[tokio::test]
+    async fn test_multipart_upload() -> anyhow::Result<()> {
+        init_test_logger();
+        let (s3, client) = create_in_memory_bucket_client();
+        let content = generate_test_content(5 * 1024 * 1024 + 100);
+        let stream = futures::stream::iter(
+            content
+                .chunks(64 * 1024)
+                .map(|chunk| Ok::<_, std::io::Error>(bytes::Bytes::copy_from_slice(chunk)))
+                .collect::<Vec<_>>(),
+        );
+        let mut min = MinimumSizedStream::with_capacity(stream, 1024 * 1024)
+            .with_minimum_size(1024 * 1024)
+            .with_known_size(content.len());
+        let key = "test-file-multipart.txt";
+        let borrowed_tags = BorrowedTaggingSet::new(vec![
+            BorrowedTag::from(("key1", "value1")),
+            BorrowedTag::from(("key2", "value2")),
+        ]);
+        let create = CreateMultipartUpload {
+            key,
+            tags: Some(AnyTaggingSet::Borrowed(borrowed_tags)),
+            headers: PutHeaders {
+                content_type: "text/plain".into(),
+                ..Default::default()
             },
-            test::{create_test_bucket_client, init_test_logger},
-            utils::stream::MinimumSizedStream,
+            checksum_algorithm: None,
         };
 
-        #[tokio::test]
-        async fn test_multipart_upload() -> anyhow::Result<()> {
-            init_test_logger();
-            let file = tokio::fs::File::open(
-                "/media/Other/PersonalProjects/RustProjects/rust-tests/test_file.txt",
-            )
-            .await?;
-            let stream = tokio_util::io::ReaderStream::new(file);
-            let mut min = MinimumSizedStream::with_capacity(stream, 1024 * 1024)
-                .with_known_size(5 * 1024 * 1024);
-            let client = create_test_bucket_client();
-            let key = "test-file-multipart.txt";
-            let borrowed_tags = BorrowedTaggingSet::new(vec![
-                BorrowedTag::from(("key1", "value1")),
-                BorrowedTag::from(("key2", "value2")),
-            ]);
-            let create = CreateMultipartUpload {
+        let response = client.execute_command(create).await?;
+        assert!(
+            response.status().is_success(),
+            "Failed to create multipart upload: {:?}",
+            response
+        );
+
+        let initate: InitiateMultipartUploadResult =
+            quick_xml::de::from_str(&response.text().await?)?;
+        let mut parts = Vec::new();
+        while let Some(bytes) = min.try_next().await? {
+            let part = PutPart {
                 key,
-                tags: Some(AnyTaggingSet::Borrowed(borrowed_tags)),
-                headers: PutHeaders {
-                    content_type: "text/plain".into(),
-                    ..Default::default()
-                },
+                part_number: parts.len() as u32 + 1,
+                upload_id: Cow::Borrowed(&initate.upload_id),
+                content: S3CommandBody::from(bytes),
+                checksum_algorithm: None,
+                content_md5: false,
             };
-
-            let response = client.execute_command(create).await?;
+            let response = client.execute_command(part).await?;
             assert!(
                 response.status().is_success(),
-                "Failed to create multipart upload: {:?}",
+                "Failed to upload part: {:?}",
                 response
             );
+            let etag = response
+                .headers()
+                .get(ETAG)
+                .expect("Missing ETag header")
+                .to_str()?
+                .to_owned();
+            parts.push(Part {
+                number: parts.len() as u32 + 1,
+                etag,
+                ..Default::default()
+            });
+        }
+        assert!(parts.len() > 1, "Expected more than one part");
 
-            let initate: InitiateMultipartUploadResult =
-                quick_xml::de::from_str(&response.text().await?)?;
-            let mut parts = Vec::new();
-            while let Some(bytes) = min.try_next().await? {
-                let part = PutPart {
+        let complete = crate::command::put::CompleteMultipartUpload {
+            key,
+            upload_id: Cow::Borrowed(&initate.upload_id),
+            content: tux_io_s3_types::multi_part::CompleteMultipartUpload { parts },
+        };
+        let response = client.execute_command(complete).await?;
+        assert!(
+            response.status().is_success(),
+            "Failed to complete multipart upload: {:?}",
+            response
+        );
+        let object = s3
+            .get_object(IN_MEMORY_BUCKET, key)
+            .expect("Object was not stored");
+        assert_eq!(object.content, content);
+        assert_eq!(object.content_type, "text/plain");
+        assert_eq!(object.tags.len(), 2);
+        assert_eq!(s3.pending_uploads(IN_MEMORY_BUCKET), 0);
+        Ok(())
+    }
+    #[tokio::test]
+    async fn multipart_checksums() -> anyhow::Result<()> {
+        init_test_logger();
+        let (s3, client) = create_in_memory_bucket_client();
+        let key = "checksums.bin";
+        let algorithm = ChecksumAlgorithm::Sha256;
+        let response = client
+            .execute_command(CreateMultipartUpload {
+                key,
+                tags: None,
+                headers: PutHeaders::default(),
+                checksum_algorithm: Some(algorithm),
+            })
+            .await?;
+        let initate: InitiateMultipartUploadResult =
+            quick_xml::de::from_str(&response.text().await?)?;
+        let content = generate_test_content(5 * 1024 * 1024 + 100);
+        let mut parts = Vec::new();
+        for (index, chunk) in content.chunks(5 * 1024 * 1024).enumerate() {
+            let number = index as u32 + 1;
+            let response = client
+                .execute_command(PutPart {
                     key,
-                    part_number: parts.len() as u32 + 1,
+                    part_number: number,
                     upload_id: Cow::Borrowed(&initate.upload_id),
-                    content: S3CommandBody::from(bytes),
-                };
-                let response = client.execute_command(part).await?;
-                assert!(
-                    response.status().is_success(),
-                    "Failed to upload part: {:?}",
-                    response
-                );
-                let etag = response
-                    .headers()
-                    .get(ETAG)
-                    .expect("Missing ETag header")
-                    .to_str()?
-                    .to_owned();
-                parts.push(Part {
-                    number: parts.len() as u32 + 1,
-                    etag,
-                });
-            }
-
-            let complete = crate::command::put::CompleteMultipartUpload {
-                key,
-                upload_id: Cow::Borrowed(&initate.upload_id),
-                content: tux_io_s3_types::multi_part::CompleteMultipartUpload { parts },
-            };
-            let response = client.execute_command(complete).await?;
-            assert!(
-                response.status().is_success(),
-                "Failed to complete multipart upload: {:?}",
-                response
+                    content: S3CommandBody::from(chunk.to_vec()),
+                    checksum_algorithm: Some(algorithm),
+                    content_md5: false,
+                })
+                .await?;
+            assert!(response.status().is_success());
+            let part_checksum = response.headers()[algorithm.header_name()].to_str()?;
+            assert_eq!(part_checksum, checksum(algorithm, chunk));
+            parts.push(
+                Part {
+                    number,
+                    etag: response.headers()[ETAG].to_str()?.to_owned(),
+                    ..Default::default()
+                }
+                .with_checksum(algorithm, part_checksum),
             );
-            Ok(())
         }
+        let complete = |parts| CompleteMultipartUpload {
+            key,
+            upload_id: Cow::Borrowed(&initate.upload_id),
+            content: tux_io_s3_types::multi_part::CompleteMultipartUpload { parts },
+        };
+        let mut wrong = parts.clone();
+        wrong[1].set_checksum(algorithm, checksum(algorithm, b"other"));
+        let response = client.execute_command(complete(wrong)).await?;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = client.execute_command(complete(parts)).await?;
+        assert!(response.status().is_success());
+        let object = s3.get_object(IN_MEMORY_BUCKET, key).expect("Object");
+        assert_eq!(object.content, content);
+        let (object_algorithm, object_checksum) = object.checksum.expect("Checksum");
+        assert_eq!(object_algorithm, algorithm);
+        assert!(object_checksum.ends_with("-2"));
+        Ok(())
+    }
+    #[tokio::test]
+    async fn abort_multipart_upload() -> anyhow::Result<()> {
+        init_test_logger();
+        let (s3, client) = create_in_memory_bucket_client();
+        let key = "aborted.txt";
+        let response = client
+            .execute_command(CreateMultipartUpload {
+                key,
+                tags: None,
+                headers: PutHeaders::default(),
+                checksum_algorithm: None,
+            })
+            .await?;
+        let initate: InitiateMultipartUploadResult =
+            quick_xml::de::from_str(&response.text().await?)?;
+        assert_eq!(s3.pending_uploads(IN_MEMORY_BUCKET), 1);
+
+        let abort = || AbortMultipartUpload {
+            key,
+            upload_id: Cow::Borrowed(&initate.upload_id),
+        };
+        let response = client.execute_command(abort()).await?;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(s3.pending_uploads(IN_MEMORY_BUCKET), 0);
+
+        let response = client.execute_command(abort()).await?;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        Ok(())
     }
 }
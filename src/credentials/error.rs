@@ -12,4 +12,8 @@ pub enum SigningRelatedError {
     InvalidHeader(#[from] InvalidHeaderValue),
     #[error("Missing builder parameter: {0}")]
     MissingBuilderParameter(&'static str),
+    #[error("Unable to serialize post policy: {0}")]
+    PostPolicy(serde_json::Error),
+    #[error("Expiration of the post policy is out of range")]
+    ExpirationOutOfRange,
 }
@@ -3,6 +3,8 @@ use hmac::Mac;
 use crate::credentials::error::SigningRelatedError;
 pub mod error;
 pub mod header;
+pub mod post_policy;
+pub mod presign;
 pub mod provider;
 pub mod signing;
 pub type Hmac256 = hmac::Hmac<sha2::Sha256>;
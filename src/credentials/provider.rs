@@ -79,9 +79,11 @@ impl From<CredentialsVariants> for CredentialsProvider {
             CredentialsVariants::AccessAndSecret {
                 access_key,
                 secret_key,
+                session_token,
             } => CredentialsProvider::Static(Credentials {
                 access_key,
                 secret_key,
+                session_token,
             }),
             CredentialsVariants::AssumeRoleWithWebIdentity {
                 role_arn,
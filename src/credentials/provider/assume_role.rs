@@ -335,6 +335,10 @@ mod tests {
             creds.secret_key,
             "wJalrXUtnFEMI/K7MDENG/bPxRfiCYzEXAMPLEKEY"
         );
+        assert_eq!(
+            creds.session_token.as_deref(),
+            Some("AQoDYXdzEE0a8ANXXXXXXXXNO1ewxE5TijQyp+IEXAMPLE")
+        );
 
         // Second call should use cached credentials
         let result = provider.provide(mock_client.clone()).await;
@@ -3,10 +3,10 @@ use std::fmt::Display;
 pub use http;
 use http::{HeaderName, HeaderValue, header::InvalidHeaderValue};
 use thiserror::Error;
-use tux_io_s3_types::S3ContentError;
+use tux_io_s3_types::{S3ContentError, checksum::ChecksumAlgorithm, error::S3ErrorCode};
 
 use crate::{
-    client::HttpResponseError,
+    client::{HttpResponseError, S3ServiceError},
     credentials::{error::SigningRelatedError, provider::CredentialsProviderError},
 };
 pub use tux_io_s3_types as types;
@@ -17,6 +17,8 @@ pub mod credentials;
 pub mod test;
 pub mod utils;
 pub const EMPTY_HASH: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+/// `x-amz-content-sha256` value of requests where the payload is not part of the signature
+pub const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
 pub type S3Result<T> = Result<T, S3Error>;
 #[derive(Debug, Error)]
 pub enum S3Error {
@@ -34,22 +36,68 @@ pub enum S3Error {
     SigningRelatedError(#[from] SigningRelatedError),
     #[error(transparent)]
     HttpError(Box<HttpResponseError>),
+    #[error(transparent)]
+    Service(Box<S3ServiceError>),
     #[error("Chunk Must be atleast 8KB")]
     ChunkTooSmall(usize),
     #[error(transparent)]
     CredentialsError(#[from] CredentialsProviderError),
     #[error("Error Reading Body From Stream")]
     BodyReadError(Box<dyn std::error::Error + Send + Sync>),
+    #[error("Interceptor error: {0}")]
+    Interceptor(Box<dyn std::error::Error + Send + Sync>),
+    #[error("Unable to resolve endpoint: {0}")]
+    Endpoint(std::borrow::Cow<'static, str>),
+    #[error("Presigned URLs must expire within 1 second to 7 days. Got {0:?}")]
+    InvalidPresignExpiration(std::time::Duration),
+    #[error("A multipart upload has 1 to 10000 parts. Got {0}")]
+    InvalidPartCount(u32),
+    #[error(
+        "Presigned parts can not send a {0} checksum. The value is not known when the url is signed"
+    )]
+    PresignedPartChecksum(ChecksumAlgorithm),
+    #[error("Reported parts do not match the uploaded parts: {0}")]
+    PartsMismatch(String),
+    #[error("Unsigned payloads are refused over plain HTTP")]
+    UnsignedPayloadOverHttp,
+    #[error("Content-MD5 can not be calculated for a streamed body")]
+    ContentMd5ForStream,
+    #[error("ListObjectsV2 continuation tokens can not be sent to ListObjects (V1)")]
+    ContinuationTokenWithV1,
+    #[error(transparent)]
+    ChecksumMismatch(#[from] ChecksumMismatch),
 }
 impl S3Error {
     /// Returns the HTTP Status Code Related to this error if applicable.
     pub fn status_code(&self) -> Option<http::StatusCode> {
         match self {
             S3Error::HttpError(err) => err.status_code(),
+            S3Error::Service(err) => Some(err.status),
             S3Error::CredentialsError(err) => err.status_code(),
             _ => None,
         }
     }
+    /// Returns the S3 Error Code if this error came from an S3 error response.
+    pub fn error_code(&self) -> Option<&S3ErrorCode> {
+        match self {
+            S3Error::Service(err) => Some(&err.code),
+            _ => None,
+        }
+    }
+    /// Converts a failed response into an error.
+    ///
+    /// If the body contains an S3 `<Error>` document [S3Error::Service] is returned. Otherwise [S3Error::HttpError]
+    pub async fn from_response(response: reqwest::Response) -> Self {
+        match client::service_error_from_response(response).await {
+            Ok(service_error) => S3Error::Service(Box::new(service_error)),
+            Err(err) => S3Error::from(err),
+        }
+    }
+}
+impl From<S3ServiceError> for S3Error {
+    fn from(error: S3ServiceError) -> Self {
+        S3Error::Service(Box::new(error))
+    }
 }
 impl From<HttpResponseError> for S3Error {
     fn from(error: HttpResponseError) -> Self {
@@ -62,6 +110,14 @@ impl From<reqwest::Error> for S3Error {
         Self::from(response)
     }
 }
+/// The checksum of a downloaded body does not match the checksum S3 reported.
+#[derive(Debug, Error)]
+#[error("{algorithm} checksum mismatch. Expected {expected} but calculated {calculated}")]
+pub struct ChecksumMismatch {
+    pub algorithm: ChecksumAlgorithm,
+    pub expected: String,
+    pub calculated: String,
+}
 #[derive(Debug, Error)]
 pub struct InvalidResponseHeader {
     pub name: HeaderName,
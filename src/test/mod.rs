@@ -3,10 +3,13 @@ use std::{
     sync::{Arc, Once},
 };
 
+use bytes::Bytes;
 use serde::{Deserialize, Serialize};
 use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
 use tux_io_s3_types::{credentials::Credentials, region::S3Region};
 
+use crate::client::{BucketClient, S3ClientBuilder, http_client::InMemoryS3};
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TestConfig {
     pub region: S3Region,
@@ -71,7 +74,38 @@ pub fn create_test_client() -> crate::client::S3Client {
         .build()
         .expect("Failed to create test bucket client")
 }
-
+/// Bucket created by [create_in_memory_bucket_client]
+pub const IN_MEMORY_BUCKET: &str = "test-bucket";
+/// Creates a path style [BucketClient] backed by a new [InMemoryS3] with an empty [IN_MEMORY_BUCKET]
+pub fn create_in_memory_bucket_client() -> (InMemoryS3, BucketClient<InMemoryS3>) {
+    let s3 = InMemoryS3::new().with_bucket(IN_MEMORY_BUCKET);
+    let client = in_memory_client_builder(&s3)
+        .bucket_client(IN_MEMORY_BUCKET)
+        .expect("Failed to create in memory bucket client");
+    (s3, client)
+}
+/// A path style builder for `http://localhost:9000` that sends requests to the given [InMemoryS3]
+pub fn in_memory_client_builder(s3: &InMemoryS3) -> S3ClientBuilder<InMemoryS3> {
+    let region: tux_io_s3_types::region::CustomRegion = "http://localhost:9000"
+        .parse()
+        .expect("Failed to parse test region");
+    S3ClientBuilder::default()
+        .with_region(region)
+        .with_access_type(crate::client::AccessType::PathStyle)
+        .with_credentials(Arc::new(
+            Credentials {
+                access_key: "access_key".into(),
+                secret_key: "secret_key".into(),
+                session_token: None,
+            }
+            .into(),
+        ))
+        .with_transport(s3.clone())
+}
+/// Content that is large enough to be streamed in multiple chunks
+pub fn generate_test_content(size: usize) -> Bytes {
+    (0..size).map(|index| (index % 251) as u8).collect()
+}
 mod tests {
     use crate::test::init_test_logger;
 
@@ -0,0 +1,165 @@
+//! Calculation of the flexible checksums in [ChecksumAlgorithm]
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use bytes::Bytes;
+use crc::{CRC_32_ISCSI, CRC_32_ISO_HDLC, CRC_64_NVME, Crc, Digest};
+use futures::Stream;
+use pin_project::pin_project;
+use sha1::Sha1;
+use sha2::{Digest as _, Sha256};
+use tux_io_s3_types::checksum::ChecksumAlgorithm;
+
+use crate::{ChecksumMismatch, S3Error};
+static CRC32: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+static CRC32C: Crc<u32> = Crc::<u32>::new(&CRC_32_ISCSI);
+static CRC64NVME: Crc<u64> = Crc::<u64>::new(&CRC_64_NVME);
+/// Calculates a checksum over data that arrives in pieces
+pub enum ChecksumHasher {
+    Crc32(Digest<'static, u32>),
+    Crc32c(Digest<'static, u32>),
+    Sha1(Sha1),
+    Sha256(Sha256),
+    Crc64Nvme(Digest<'static, u64>),
+}
+impl ChecksumHasher {
+    pub fn new(algorithm: ChecksumAlgorithm) -> Self {
+        match algorithm {
+            ChecksumAlgorithm::Crc32 => ChecksumHasher::Crc32(CRC32.digest()),
+            ChecksumAlgorithm::Crc32c => ChecksumHasher::Crc32c(CRC32C.digest()),
+            ChecksumAlgorithm::Sha1 => ChecksumHasher::Sha1(Sha1::new()),
+            ChecksumAlgorithm::Sha256 => ChecksumHasher::Sha256(Sha256::new()),
+            ChecksumAlgorithm::Crc64Nvme => ChecksumHasher::Crc64Nvme(CRC64NVME.digest()),
+        }
+    }
+    pub fn algorithm(&self) -> ChecksumAlgorithm {
+        match self {
+            ChecksumHasher::Crc32(_) => ChecksumAlgorithm::Crc32,
+            ChecksumHasher::Crc32c(_) => ChecksumAlgorithm::Crc32c,
+            ChecksumHasher::Sha1(_) => ChecksumAlgorithm::Sha1,
+            ChecksumHasher::Sha256(_) => ChecksumAlgorithm::Sha256,
+            ChecksumHasher::Crc64Nvme(_) => ChecksumAlgorithm::Crc64Nvme,
+        }
+    }
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            ChecksumHasher::Crc32(digest) | ChecksumHasher::Crc32c(digest) => digest.update(data),
+            ChecksumHasher::Sha1(hasher) => hasher.update(data),
+            ChecksumHasher::Sha256(hasher) => hasher.update(data),
+            ChecksumHasher::Crc64Nvme(digest) => digest.update(data),
+        }
+    }
+    /// The big-endian bytes of the checksum
+    pub fn finalize_bytes(self) -> Vec<u8> {
+        match self {
+            ChecksumHasher::Crc32(digest) | ChecksumHasher::Crc32c(digest) => {
+                digest.finalize().to_be_bytes().to_vec()
+            }
+            ChecksumHasher::Sha1(hasher) => hasher.finalize().to_vec(),
+            ChecksumHasher::Sha256(hasher) => hasher.finalize().to_vec(),
+            ChecksumHasher::Crc64Nvme(digest) => digest.finalize().to_be_bytes().to_vec(),
+        }
+    }
+    /// The base64 encoded checksum as it is sent in the `x-amz-checksum-*` headers
+    pub fn finalize(self) -> String {
+        STANDARD.encode(self.finalize_bytes())
+    }
+}
+/// Calculates the base64 encoded checksum of `data`
+pub fn checksum(algorithm: ChecksumAlgorithm, data: &[u8]) -> String {
+    let mut hasher = ChecksumHasher::new(algorithm);
+    hasher.update(data);
+    hasher.finalize()
+}
+/// Checks the checksum of a body once it has been read to the end.
+///
+/// Yields [S3Error::ChecksumMismatch] as the last item if the checksum does not match.
+#[pin_project]
+pub struct ChecksumStream<S> {
+    #[pin]
+    stream: S,
+    hasher: Option<ChecksumHasher>,
+    expected: String,
+}
+impl<S> ChecksumStream<S> {
+    pub fn new(stream: S, algorithm: ChecksumAlgorithm, expected: impl Into<String>) -> Self {
+        Self {
+            stream,
+            hasher: Some(ChecksumHasher::new(algorithm)),
+            expected: expected.into(),
+        }
+    }
+}
+impl<S, E> Stream for ChecksumStream<S>
+where
+    S: Stream<Item = Result<Bytes, E>>,
+    S3Error: From<E>,
+{
+    type Item = Result<Bytes, S3Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        match this.stream.poll_next(cx) {
+            Poll::Ready(Some(Ok(data))) => {
+                if let Some(hasher) = this.hasher {
+                    hasher.update(&data);
+                }
+                Poll::Ready(Some(Ok(data)))
+            }
+            Poll::Ready(Some(Err(err))) => Poll::Ready(Some(Err(err.into()))),
+            Poll::Ready(None) => {
+                let Some(hasher) = this.hasher.take() else {
+                    return Poll::Ready(None);
+                };
+                let algorithm = hasher.algorithm();
+                let calculated = hasher.finalize();
+                if calculated == *this.expected {
+                    return Poll::Ready(None);
+                }
+                Poll::Ready(Some(Err(ChecksumMismatch {
+                    algorithm,
+                    expected: std::mem::take(this.expected),
+                    calculated,
+                }
+                .into())))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_checksums() {
+        let data = b"Hello World";
+        for (algorithm, expected) in [
+            (ChecksumAlgorithm::Crc32, "ShexVg=="),
+            (ChecksumAlgorithm::Crc32c, "aR2qLw=="),
+            (ChecksumAlgorithm::Crc64Nvme, "ZZYyy35L4mE="),
+            (ChecksumAlgorithm::Sha1, "Ck1VqNd45QIvq3AZd8XYQLvEhtA="),
+            (
+                ChecksumAlgorithm::Sha256,
+                "pZGm1Av0IEBKARczz7exkNYsZb8LzaMrV7J32a2fFG4=",
+            ),
+        ] {
+            assert_eq!(checksum(algorithm, data), expected, "{algorithm}");
+        }
+        // The check value of the CRC-64/NVME catalog entry, 0xAE8B14860A799888
+        assert_eq!(
+            checksum(ChecksumAlgorithm::Crc64Nvme, b"123456789"),
+            "rosUhgp5mIg="
+        );
+        let mut hasher = ChecksumHasher::new(ChecksumAlgorithm::Crc64Nvme);
+        hasher.update(b"Hello ");
+        hasher.update(b"World");
+        assert_eq!(
+            hasher.finalize(),
+            checksum(ChecksumAlgorithm::Crc64Nvme, data)
+        );
+    }
+}
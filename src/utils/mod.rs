@@ -1,4 +1,5 @@
 use http::HeaderValue;
+pub mod checksum;
 pub mod header;
 pub mod stream;
 pub mod url;
@@ -0,0 +1,76 @@
+//! Flexible checksums of objects and parts
+use std::{fmt::Display, str::FromStr};
+
+use http::HeaderName;
+use serde::{Deserialize, Serialize};
+
+use crate::headers::{
+    X_AMZ_CHECKSUM_CRC32, X_AMZ_CHECKSUM_CRC32C, X_AMZ_CHECKSUM_CRC64NVME, X_AMZ_CHECKSUM_SHA1,
+    X_AMZ_CHECKSUM_SHA256,
+};
+/// An algorithm S3 can verify the integrity of an upload with.
+///
+/// [AWS Docs](https://docs.aws.amazon.com/AmazonS3/latest/userguide/checking-object-integrity.html)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ChecksumAlgorithm {
+    #[serde(rename = "CRC32")]
+    Crc32,
+    #[serde(rename = "CRC32C")]
+    Crc32c,
+    #[serde(rename = "SHA1")]
+    Sha1,
+    #[serde(rename = "SHA256")]
+    Sha256,
+    #[serde(rename = "CRC64NVME")]
+    Crc64Nvme,
+}
+impl ChecksumAlgorithm {
+    pub const ALL: [ChecksumAlgorithm; 5] = [
+        ChecksumAlgorithm::Crc32,
+        ChecksumAlgorithm::Crc32c,
+        ChecksumAlgorithm::Sha1,
+        ChecksumAlgorithm::Sha256,
+        ChecksumAlgorithm::Crc64Nvme,
+    ];
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Crc32 => "CRC32",
+            ChecksumAlgorithm::Crc32c => "CRC32C",
+            ChecksumAlgorithm::Sha1 => "SHA1",
+            ChecksumAlgorithm::Sha256 => "SHA256",
+            ChecksumAlgorithm::Crc64Nvme => "CRC64NVME",
+        }
+    }
+    /// The `x-amz-checksum-*` header that carries the checksum
+    pub fn header_name(&self) -> HeaderName {
+        match self {
+            ChecksumAlgorithm::Crc32 => X_AMZ_CHECKSUM_CRC32,
+            ChecksumAlgorithm::Crc32c => X_AMZ_CHECKSUM_CRC32C,
+            ChecksumAlgorithm::Sha1 => X_AMZ_CHECKSUM_SHA1,
+            ChecksumAlgorithm::Sha256 => X_AMZ_CHECKSUM_SHA256,
+            ChecksumAlgorithm::Crc64Nvme => X_AMZ_CHECKSUM_CRC64NVME,
+        }
+    }
+}
+impl AsRef<str> for ChecksumAlgorithm {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+impl Display for ChecksumAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown checksum algorithm: {0}")]
+pub struct UnknownChecksumAlgorithm(pub String);
+impl FromStr for ChecksumAlgorithm {
+    type Err = UnknownChecksumAlgorithm;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ChecksumAlgorithm::ALL
+            .into_iter()
+            .find(|algorithm| algorithm.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| UnknownChecksumAlgorithm(s.to_owned()))
+    }
+}
@@ -1,7 +1,9 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 use url::Url;
+
+use crate::region::OfficialRegion;
 mod assume_role_with_web_identity;
 pub use assume_role_with_web_identity::*;
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Default)]
@@ -10,12 +12,18 @@ pub struct Credentials {
     pub access_key: String,
     /// AWS Secret Access Key
     pub secret_key: String,
+    /// AWS Session Token
+    ///
+    /// Required when using temporary credentials. Such as the ones returned by STS
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_token: Option<String>,
 }
 impl From<&StsResponseCredentials> for Credentials {
     fn from(value: &StsResponseCredentials) -> Self {
         Self {
             access_key: value.access_key_id.clone(),
             secret_key: value.secret_access_key.clone(),
+            session_token: Some(value.session_token.clone()),
         }
     }
 }
@@ -25,6 +33,8 @@ pub enum CredentialsVariants {
     AccessAndSecret {
         access_key: String,
         secret_key: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        session_token: Option<String>,
     },
     AssumeRoleWithWebIdentity {
         role_arn: String,
@@ -41,6 +51,7 @@ impl CredentialsVariants {
             return Some(Self::AccessAndSecret {
                 access_key,
                 secret_key,
+                session_token: std::env::var("AWS_SESSION_TOKEN").ok(),
             });
         }
         let role_arn = std::env::var("AWS_ROLE_ARN").ok()?;
@@ -48,8 +59,15 @@ impl CredentialsVariants {
         if let (Some(role_arn), Some(web_identity_token_file)) =
             (Some(role_arn), Some(web_identity_token_file))
         {
-            let sts_endpoint = std::env::var("AWS_STS_ENDPOINT")
-                .unwrap_or_else(|_| "https://sts.amazonaws.com".to_string());
+            // Regions outside of the `aws` partition can not use the global endpoint
+            let sts_endpoint = std::env::var("AWS_STS_ENDPOINT").unwrap_or_else(|_| {
+                std::env::var("AWS_REGION")
+                    .ok()
+                    .and_then(|region| OfficialRegion::from_str(&region).ok())
+                    .map(|region| region.sts_endpoint())
+                    .unwrap_or("https://sts.amazonaws.com")
+                    .to_string()
+            });
             let sts_endpoint = Url::parse(&sts_endpoint).ok()?;
             return Some(Self::AssumeRoleWithWebIdentity {
                 role_arn,
@@ -61,3 +79,24 @@ impl CredentialsVariants {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+
+    use super::*;
+
+    #[test]
+    fn sts_credentials_keep_session_token() {
+        let sts = StsResponseCredentials {
+            session_token: "session-token".to_string(),
+            secret_access_key: "secret".to_string(),
+            expiration: DateTime::parse_from_rfc3339("2014-10-24T23:00:23Z").unwrap(),
+            access_key_id: "access".to_string(),
+        };
+        let credentials = Credentials::from(&sts);
+        assert_eq!(credentials.access_key, "access");
+        assert_eq!(credentials.secret_key, "secret");
+        assert_eq!(credentials.session_token.as_deref(), Some("session-token"));
+    }
+}
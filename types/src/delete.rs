@@ -0,0 +1,102 @@
+//! Bodies of the `DeleteObjects` request and response
+use serde::{Deserialize, Serialize};
+/// The objects removed by a `DeleteObjects` request. Up to 1000 per request.
+///
+/// [AWS Docs](https://docs.aws.amazon.com/AmazonS3/latest/API/API_DeleteObjects.html)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Delete {
+    #[serde(rename = "Object", default)]
+    pub objects: Vec<ObjectIdentifier>,
+    /// Only the objects that could not be deleted are returned
+    #[serde(rename = "Quiet", default, skip_serializing_if = "std::ops::Not::not")]
+    pub quiet: bool,
+}
+impl Delete {
+    pub fn new(objects: impl IntoIterator<Item = impl Into<ObjectIdentifier>>) -> Self {
+        Self {
+            objects: objects.into_iter().map(Into::into).collect(),
+            quiet: false,
+        }
+    }
+    pub fn with_quiet(mut self, quiet: bool) -> Self {
+        self.quiet = quiet;
+        self
+    }
+}
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ObjectIdentifier {
+    pub key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_id: Option<String>,
+}
+impl From<&str> for ObjectIdentifier {
+    fn from(key: &str) -> Self {
+        Self {
+            key: key.to_owned(),
+            version_id: None,
+        }
+    }
+}
+impl From<String> for ObjectIdentifier {
+    fn from(key: String) -> Self {
+        Self {
+            key,
+            version_id: None,
+        }
+    }
+}
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeleteResult {
+    #[serde(rename = "Deleted", default)]
+    pub deleted: Vec<DeletedObject>,
+    #[serde(rename = "Error", default)]
+    pub errors: Vec<DeleteError>,
+}
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DeletedObject {
+    pub key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delete_marker: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delete_marker_version_id: Option<String>,
+}
+/// An object that could not be deleted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DeleteError {
+    pub key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_id: Option<String>,
+    pub code: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delete_xml() {
+        let delete = Delete::new(["a.txt", "b.txt"]).with_quiet(true);
+        let xml = quick_xml::se::to_string(&delete).unwrap();
+        assert_eq!(
+            xml,
+            "<Delete><Object><Key>a.txt</Key></Object><Object><Key>b.txt</Key></Object><Quiet>true</Quiet></Delete>"
+        );
+        assert_eq!(quick_xml::de::from_str::<Delete>(&xml).unwrap(), delete);
+
+        let result: DeleteResult = quick_xml::de::from_str(
+            r#"<DeleteResult>
+                <Deleted><Key>a.txt</Key></Deleted>
+                <Error><Key>b.txt</Key><Code>AccessDenied</Code><Message>Access Denied</Message></Error>
+            </DeleteResult>"#,
+        )
+        .unwrap();
+        assert_eq!(result.deleted[0].key, "a.txt");
+        assert_eq!(result.errors[0].code, "AccessDenied");
+    }
+}
@@ -1,7 +1,137 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt::Display};
 
-use serde::{Deserialize, Deserializer, Serialize, ser::SerializeMap};
-#[derive(Debug)]
+use serde::{Deserialize, Deserializer, Serialize, Serializer, ser::SerializeMap};
+macro_rules! s3_error_code {
+    (
+        $(
+            $name:ident
+        ),* $(,)?
+    ) => {
+        /// Error codes returned by S3 in the `<Code>` element of an error response.
+        ///
+        /// [AWS Docs](https://docs.aws.amazon.com/AmazonS3/latest/API/ErrorResponses.html#ErrorCodeList)
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum S3ErrorCode {
+            $(
+                $name,
+            )*
+            Other(String),
+        }
+        impl S3ErrorCode {
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(
+                        S3ErrorCode::$name => stringify!($name),
+                    )*
+                    S3ErrorCode::Other(v) => v,
+                }
+            }
+        }
+        impl From<&str> for S3ErrorCode {
+            fn from(value: &str) -> Self {
+                match value {
+                    $(
+                        stringify!($name) => S3ErrorCode::$name,
+                    )*
+                    other => S3ErrorCode::Other(other.to_string()),
+                }
+            }
+        }
+        impl From<String> for S3ErrorCode {
+            fn from(value: String) -> Self {
+                match value.as_str() {
+                    $(
+                        stringify!($name) => S3ErrorCode::$name,
+                    )*
+                    _ => S3ErrorCode::Other(value),
+                }
+            }
+        }
+    };
+}
+s3_error_code! {
+    AccessDenied,
+    AccountProblem,
+    AuthorizationHeaderMalformed,
+    AuthorizationQueryParametersError,
+    BadDigest,
+    BucketAlreadyExists,
+    BucketAlreadyOwnedByYou,
+    BucketNotEmpty,
+    EntityTooLarge,
+    EntityTooSmall,
+    ExpiredToken,
+    IncompleteBody,
+    InternalError,
+    InvalidAccessKeyId,
+    InvalidArgument,
+    InvalidBucketName,
+    InvalidDigest,
+    InvalidObjectState,
+    InvalidPart,
+    InvalidPartOrder,
+    InvalidRange,
+    InvalidRequest,
+    InvalidToken,
+    KeyTooLongError,
+    MalformedXML,
+    MethodNotAllowed,
+    MissingContentLength,
+    NoSuchBucket,
+    NoSuchKey,
+    NoSuchUpload,
+    NoSuchVersion,
+    NotImplemented,
+    PermanentRedirect,
+    PreconditionFailed,
+    RequestTimeTooSkewed,
+    RequestTimeout,
+    ServiceUnavailable,
+    SignatureDoesNotMatch,
+    SlowDown,
+    TemporaryRedirect,
+    Throttling,
+    ThrottlingException,
+    TooManyBuckets,
+}
+impl S3ErrorCode {
+    /// Errors that are caused by the service being busy or the request taking too long.
+    ///
+    /// Sending the same request again may succeed.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            S3ErrorCode::InternalError
+                | S3ErrorCode::RequestTimeout
+                | S3ErrorCode::ServiceUnavailable
+                | S3ErrorCode::SlowDown
+                | S3ErrorCode::Throttling
+                | S3ErrorCode::ThrottlingException
+        )
+    }
+}
+impl Display for S3ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+impl Serialize for S3ErrorCode {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+impl<'de> Deserialize<'de> for S3ErrorCode {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(S3ErrorCode::from)
+    }
+}
+#[derive(Debug, Clone)]
 pub struct Error {
     pub code: String,
     pub message: Option<String>,
@@ -88,6 +218,12 @@ impl<'de> Deserialize<'de> for Error {
         deserializer.deserialize_map(ErrorVisitor)
     }
 }
+impl Error {
+    /// Returns [Error::code] as a [S3ErrorCode]
+    pub fn error_code(&self) -> S3ErrorCode {
+        S3ErrorCode::from(self.code.as_str())
+    }
+}
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ErrorAttribute {
@@ -114,5 +250,24 @@ mod tests {
 
         let error: super::Error = quick_xml::de::from_str(xml).unwrap();
         assert_eq!(error.code, "NoSuchBucket");
+        assert_eq!(error.error_code(), super::S3ErrorCode::NoSuchBucket);
+        assert_eq!(
+            error.attributes.get("Key").map(String::as_str),
+            Some("path/to/object")
+        );
+    }
+    #[test]
+    fn error_code_round_trip() {
+        use super::S3ErrorCode;
+        assert_eq!(S3ErrorCode::from("NoSuchKey"), S3ErrorCode::NoSuchKey);
+        assert_eq!(S3ErrorCode::NoSuchKey.to_string(), "NoSuchKey");
+        assert_eq!(
+            S3ErrorCode::from("XAmzContentSHA256Mismatch"),
+            S3ErrorCode::Other("XAmzContentSHA256Mismatch".to_string())
+        );
+        assert_eq!(
+            S3ErrorCode::Other("XAmzContentSHA256Mismatch".to_string()).as_str(),
+            "XAmzContentSHA256Mismatch"
+        );
     }
 }
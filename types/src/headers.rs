@@ -46,6 +46,37 @@ s3_headers! {
     X_AMZ_RENAME_SOURCE => "x-amz-rename-source",
     /// The `x-amz-copy-source` header
     X_AMZ_COPY_SOURCE => "x-amz-copy-source",
+    /// The `x-amz-bucket-region` header
+    /// Region the bucket is in. Sent with redirects and `HeadBucket` responses
+    X_AMZ_BUCKET_REGION => "x-amz-bucket-region",
+    /// The `x-amz-checksum-crc32` header
+    X_AMZ_CHECKSUM_CRC32 => "x-amz-checksum-crc32",
+    /// The `x-amz-checksum-crc32c` header
+    X_AMZ_CHECKSUM_CRC32C => "x-amz-checksum-crc32c",
+    /// The `x-amz-checksum-sha1` header
+    X_AMZ_CHECKSUM_SHA1 => "x-amz-checksum-sha1",
+    /// The `x-amz-checksum-sha256` header
+    X_AMZ_CHECKSUM_SHA256 => "x-amz-checksum-sha256",
+    /// The `x-amz-checksum-crc64nvme` header
+    X_AMZ_CHECKSUM_CRC64NVME => "x-amz-checksum-crc64nvme",
+    /// The `x-amz-checksum-algorithm` header
+    /// Algorithm of the part checksums of a multipart upload
+    X_AMZ_CHECKSUM_ALGORITHM => "x-amz-checksum-algorithm",
+    /// The `x-amz-sdk-checksum-algorithm` header
+    /// Algorithm of the checksum sent with the request
+    X_AMZ_SDK_CHECKSUM_ALGORITHM => "x-amz-sdk-checksum-algorithm",
+    /// The `x-amz-checksum-mode` header
+    /// Set to `ENABLED` to receive the checksum of an object
+    X_AMZ_CHECKSUM_MODE => "x-amz-checksum-mode",
+    /// The `x-amz-checksum-type` header
+    /// `FULL_OBJECT` or `COMPOSITE`
+    X_AMZ_CHECKSUM_TYPE => "x-amz-checksum-type",
+    /// The `x-amz-trailer` header
+    /// Headers sent after an `aws-chunked` body
+    X_AMZ_TRAILER => "x-amz-trailer",
+    /// The `Content-MD5` header
+    /// Base64 encoded MD5 digest of the body
+    CONTENT_MD5 => "content-md5",
 
 
 
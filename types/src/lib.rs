@@ -1,4 +1,6 @@
+pub mod checksum;
 pub mod credentials;
+pub mod delete;
 //pub mod path;
 pub mod multi_part;
 pub mod region;
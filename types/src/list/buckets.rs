@@ -1,3 +1,4 @@
+use chrono::{DateTime, FixedOffset};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -5,13 +6,15 @@ use serde::{Deserialize, Serialize};
 pub struct ListAllMyBuckets {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub continuation_token: Option<String>,
+    /// Empty if the page has no buckets
+    #[serde(default)]
     pub buckets: Buckets,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub prefix: Option<String>,
 }
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct Buckets {
-    #[serde(rename = "Bucket")]
+    #[serde(rename = "Bucket", default)]
     pub buckets: Vec<Bucket>,
 }
 impl From<Vec<Bucket>> for Buckets {
@@ -22,25 +25,27 @@ impl From<Vec<Bucket>> for Buckets {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Bucket {
-    pub creation_date: String,
+    pub creation_date: DateTime<FixedOffset>,
     pub name: String,
     #[serde(rename = "BucketRegion", skip_serializing_if = "Option::is_none")]
     pub bucket_region: Option<String>,
 }
 #[cfg(test)]
 mod tests {
+    use chrono::DateTime;
+
     use crate::list::buckets::{Bucket, Buckets, ListAllMyBuckets};
 
     #[test]
     fn tests() {
         let buckets = vec![
             Bucket {
-                creation_date: "2022-01-01".into(),
+                creation_date: DateTime::parse_from_rfc3339("2022-01-01T00:00:00.000Z").unwrap(),
                 name: "bucket1".into(),
                 bucket_region: Some("us-east-1".into()),
             },
             Bucket {
-                creation_date: "2022-01-02".into(),
+                creation_date: DateTime::parse_from_rfc3339("2022-01-02T00:00:00.000Z").unwrap(),
                 name: "bucket2".into(),
                 bucket_region: Some("us-west-1".into()),
             },
@@ -56,4 +61,11 @@ mod tests {
         let from_xml: ListAllMyBuckets = quick_xml::de::from_str(&to_xml).unwrap();
         assert_eq!(list_buckets, from_xml);
     }
+    #[test]
+    fn empty_page() {
+        let xml = r#"<ListAllMyBucketsResult><Buckets></Buckets><Owner><ID>id</ID></Owner></ListAllMyBucketsResult>"#;
+        let page: ListAllMyBuckets = quick_xml::de::from_str(xml).unwrap();
+        assert!(page.buckets.buckets.is_empty());
+        assert!(page.continuation_token.is_none());
+    }
 }
@@ -7,7 +7,49 @@ pub mod object;
 pub mod prefix;
 pub mod v1;
 pub mod v2;
+/// An object or a common prefix of a listing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListEntry {
+    Object(object::Object),
+    CommonPrefix(String),
+}
+impl ListEntry {
+    /// The key of the object or the common prefix
+    pub fn key(&self) -> &str {
+        match self {
+            ListEntry::Object(object) => &object.key,
+            ListEntry::CommonPrefix(prefix) => prefix,
+        }
+    }
+}
 
+/// How keys are encoded in a listing response.
+///
+/// Keys can contain characters that are not allowed in XML 1.0. Request an encoding to list them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EncodingType {
+    #[serde(rename = "url")]
+    Url,
+}
+impl AsRef<str> for EncodingType {
+    fn as_ref(&self) -> &str {
+        match self {
+            EncodingType::Url => "url",
+        }
+    }
+}
+impl Display for EncodingType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+/// Decodes a value of a listing with [EncodingType::Url]. S3 encodes spaces as `+`
+pub(crate) fn url_decode(value: &mut String) {
+    let spaces = value.replace('+', " ");
+    *value = percent_encoding::percent_decode_str(&spaces)
+        .decode_utf8_lossy()
+        .into_owned();
+}
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
 pub enum ListType {
@@ -15,8 +15,11 @@ pub struct Object {
     pub key: String,
     pub last_modified: DateTime<FixedOffset>,
     pub size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub e_tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub storage_class: Option<StorageClass>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub owner: Option<Owner>,
 }
 #[derive(Debug, Clone, PartialEq, Eq)]
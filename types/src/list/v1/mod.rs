@@ -1,18 +1,147 @@
 use serde::{Deserialize, Serialize};
 
-use crate::list::{object::Object, prefix::CommonPrefixes};
+use crate::list::{EncodingType, ListEntry, object::Object, prefix::CommonPrefixes, url_decode};
 mod extractor;
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct ListBucketResult {
     pub is_truncated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub max_keys: Option<i32>,
     pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub marker: Option<String>,
+    /// Only returned if the request had a delimiter
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub next_marker: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub prefix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub delimiter: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub start_after: Option<String>,
+    /// Set if keys, prefixes, markers and the delimiter are encoded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding_type: Option<EncodingType>,
+    #[serde(default)]
     pub contents: Vec<Object>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub common_prefixes: Option<CommonPrefixes>,
 }
+impl ListBucketResult {
+    /// Decodes keys, prefixes, markers and the delimiter if the response is [EncodingType::Url] encoded.
+    ///
+    /// Clears [ListBucketResult::encoding_type] so the values are only decoded once.
+    pub fn decode_url_encoding(&mut self) {
+        if self.encoding_type.take() != Some(EncodingType::Url) {
+            return;
+        }
+        let objects = self.contents.iter_mut().map(|object| &mut object.key);
+        let prefixes = self
+            .common_prefixes
+            .iter_mut()
+            .flat_map(|cp| cp.prefix.iter_mut());
+        let fields = [
+            &mut self.prefix,
+            &mut self.delimiter,
+            &mut self.marker,
+            &mut self.next_marker,
+            &mut self.start_after,
+        ]
+        .into_iter()
+        .flatten();
+        objects.chain(prefixes).chain(fields).for_each(url_decode);
+    }
+    /// Number of objects and common prefixes in the page
+    pub fn entry_count(&self) -> usize {
+        self.contents.len()
+            + self
+                .common_prefixes
+                .as_ref()
+                .map_or(0, |cp| cp.prefix.len())
+    }
+    /// The marker of the next page if the listing is truncated.
+    ///
+    /// Uses [ListBucketResult::next_marker] or the last key of the page if S3 did not return one.
+    pub fn next_page_marker(&self) -> Option<&str> {
+        if !self.is_truncated {
+            return None;
+        }
+        self.next_marker.as_deref().or_else(|| {
+            let last_object = self.contents.last().map(|object| object.key.as_str());
+            let last_prefix = self
+                .common_prefixes
+                .as_ref()
+                .and_then(|cp| cp.prefix.last())
+                .map(String::as_str);
+            last_object.max(last_prefix)
+        })
+    }
+    /// Objects and common prefixes of the page sorted by key
+    pub fn into_entries(self) -> Vec<ListEntry> {
+        let mut entries: Vec<ListEntry> = self
+            .contents
+            .into_iter()
+            .map(ListEntry::Object)
+            .chain(
+                self.common_prefixes
+                    .into_iter()
+                    .flat_map(|cp| cp.prefix)
+                    .map(ListEntry::CommonPrefix),
+            )
+            .collect();
+        entries.sort_by(|a, b| a.key().cmp(b.key()));
+        entries
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn next_page_marker() {
+        let xml = r#"<ListBucketResult>
+            <IsTruncated>true</IsTruncated>
+            <Name>bucket</Name>
+            <Marker></Marker>
+            <Contents><Key>a.txt</Key><LastModified>2024-01-01T00:00:00.000Z</LastModified><Size>1</Size></Contents>
+            <Contents><Key>b.txt</Key><LastModified>2024-01-01T00:00:00.000Z</LastModified><Size>1</Size></Contents>
+        </ListBucketResult>"#;
+        let result: ListBucketResult = quick_xml::de::from_str(xml).unwrap();
+        assert_eq!(result.next_page_marker(), Some("b.txt"));
+        assert_eq!(result.entry_count(), 2);
+
+        let xml = r#"<ListBucketResult>
+            <IsTruncated>false</IsTruncated>
+            <Name>bucket</Name>
+        </ListBucketResult>"#;
+        let result: ListBucketResult = quick_xml::de::from_str(xml).unwrap();
+        assert!(result.contents.is_empty());
+        assert_eq!(result.next_page_marker(), None);
+    }
+    #[test]
+    fn url_encoded() {
+        let xml = r#"<ListBucketResult>
+            <IsTruncated>true</IsTruncated>
+            <Name>bucket</Name>
+            <Prefix>my+dir%2F</Prefix>
+            <Delimiter>%2F</Delimiter>
+            <NextMarker>my+dir%2Fsub%2F</NextMarker>
+            <EncodingType>url</EncodingType>
+            <Contents><Key>my+dir%2Fa%01%2Bb.txt</Key><LastModified>2024-01-01T00:00:00.000Z</LastModified><Size>1</Size></Contents>
+            <CommonPrefixes><Prefix>my+dir%2Fsub%2F</Prefix></CommonPrefixes>
+        </ListBucketResult>"#;
+        let mut result: ListBucketResult = quick_xml::de::from_str(xml).unwrap();
+        result.decode_url_encoding();
+        assert_eq!(result.encoding_type, None);
+        assert_eq!(result.contents[0].key, "my dir/a\u{1}+b.txt");
+        assert_eq!(result.prefix.as_deref(), Some("my dir/"));
+        assert_eq!(result.delimiter.as_deref(), Some("/"));
+        assert_eq!(result.next_marker.as_deref(), Some("my dir/sub/"));
+        assert_eq!(
+            result.common_prefixes.as_deref(),
+            Some(&vec!["my dir/sub/".to_string()])
+        );
+        result.decode_url_encoding();
+        assert_eq!(result.prefix.as_deref(), Some("my dir/"));
+    }
+}
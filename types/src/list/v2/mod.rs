@@ -1,24 +1,34 @@
 use serde::{Deserialize, Serialize};
 
-use crate::list::{object::Object, prefix::CommonPrefixes};
+use crate::list::{EncodingType, ListEntry, object::Object, prefix::CommonPrefixes, url_decode};
 mod extractor;
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct ListBucketResult {
     pub is_truncated: bool,
     /// Maximum number of keys returned in the response
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub max_keys: Option<u32>,
     /// Number of keys returned in the response
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub key_count: Option<u32>,
     /// Bucket Name
     pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub next_continuation_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub prefix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub delimiter: Option<String>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub start_after: Option<String>,
-    pub encoding_type: Option<String>,
+    /// Set if keys, prefixes, `StartAfter` and the delimiter are encoded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding_type: Option<EncodingType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub contents: Option<Vec<Object>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub common_prefixes: Option<CommonPrefixes>,
 }
 
@@ -34,4 +44,50 @@ impl ListBucketResult {
     pub fn is_empty(&self) -> bool {
         !self.has_contents() && !self.has_common_prefixes()
     }
+    /// Decodes keys, prefixes, `StartAfter` and the delimiter if the response is [EncodingType::Url] encoded.
+    ///
+    /// Clears [ListBucketResult::encoding_type] so the values are only decoded once.
+    pub fn decode_url_encoding(&mut self) {
+        if self.encoding_type.take() != Some(EncodingType::Url) {
+            return;
+        }
+        let objects = self
+            .contents
+            .iter_mut()
+            .flatten()
+            .map(|object| &mut object.key);
+        let prefixes = self
+            .common_prefixes
+            .iter_mut()
+            .flat_map(|cp| cp.prefix.iter_mut());
+        let fields = [&mut self.prefix, &mut self.delimiter, &mut self.start_after]
+            .into_iter()
+            .flatten();
+        objects.chain(prefixes).chain(fields).for_each(url_decode);
+    }
+    /// Number of objects and common prefixes in the page
+    pub fn entry_count(&self) -> usize {
+        self.contents.as_ref().map_or(0, Vec::len)
+            + self
+                .common_prefixes
+                .as_ref()
+                .map_or(0, |cp| cp.prefix.len())
+    }
+    /// Objects and common prefixes of the page sorted by key
+    pub fn into_entries(self) -> Vec<ListEntry> {
+        let mut entries: Vec<ListEntry> = self
+            .contents
+            .into_iter()
+            .flatten()
+            .map(ListEntry::Object)
+            .chain(
+                self.common_prefixes
+                    .into_iter()
+                    .flat_map(|cp| cp.prefix)
+                    .map(ListEntry::CommonPrefix),
+            )
+            .collect();
+        entries.sort_by(|a, b| a.key().cmp(b.key()));
+        entries
+    }
 }
@@ -1,4 +1,7 @@
+use chrono::{DateTime, FixedOffset};
 use serde::{Deserialize, Serialize};
+
+use crate::checksum::ChecksumAlgorithm;
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct InitiateMultipartUploadResult {
     #[serde(rename = "Bucket")]
@@ -8,38 +11,221 @@ pub struct InitiateMultipartUploadResult {
     #[serde(rename = "UploadId")]
     pub upload_id: String,
 }
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Part {
     #[serde(rename = "PartNumber")]
     pub number: u32,
     #[serde(rename = "ETag")]
     pub etag: String,
+    #[serde(
+        rename = "ChecksumCRC32",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub checksum_crc32: Option<String>,
+    #[serde(
+        rename = "ChecksumCRC32C",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub checksum_crc32c: Option<String>,
+    #[serde(
+        rename = "ChecksumSHA1",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub checksum_sha1: Option<String>,
+    #[serde(
+        rename = "ChecksumSHA256",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub checksum_sha256: Option<String>,
+    #[serde(
+        rename = "ChecksumCRC64NVME",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub checksum_crc64nvme: Option<String>,
+}
+/// Adds accessors for the `checksum_*` fields of a part
+macro_rules! part_checksums {
+    ($($type:ty),*) => {
+        $(
+            impl $type {
+                /// The base64 encoded checksum of the part for `algorithm`
+                pub fn checksum(&self, algorithm: ChecksumAlgorithm) -> Option<&str> {
+                    match algorithm {
+                        ChecksumAlgorithm::Crc32 => self.checksum_crc32.as_deref(),
+                        ChecksumAlgorithm::Crc32c => self.checksum_crc32c.as_deref(),
+                        ChecksumAlgorithm::Sha1 => self.checksum_sha1.as_deref(),
+                        ChecksumAlgorithm::Sha256 => self.checksum_sha256.as_deref(),
+                        ChecksumAlgorithm::Crc64Nvme => self.checksum_crc64nvme.as_deref(),
+                    }
+                }
+                pub fn set_checksum(&mut self, algorithm: ChecksumAlgorithm, checksum: impl Into<String>) {
+                    let checksum = Some(checksum.into());
+                    match algorithm {
+                        ChecksumAlgorithm::Crc32 => self.checksum_crc32 = checksum,
+                        ChecksumAlgorithm::Crc32c => self.checksum_crc32c = checksum,
+                        ChecksumAlgorithm::Sha1 => self.checksum_sha1 = checksum,
+                        ChecksumAlgorithm::Sha256 => self.checksum_sha256 = checksum,
+                        ChecksumAlgorithm::Crc64Nvme => self.checksum_crc64nvme = checksum,
+                    }
+                }
+                pub fn with_checksum(mut self, algorithm: ChecksumAlgorithm, checksum: impl Into<String>) -> Self {
+                    self.set_checksum(algorithm, checksum);
+                    self
+                }
+            }
+        )*
+    };
 }
+part_checksums!(Part, UploadedPart);
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CompleteMultipartUpload {
     #[serde(rename = "Part")]
     pub parts: Vec<Part>,
 }
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CompleteMultipartUploadResult {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bucket: Option<String>,
+    pub key: String,
+    #[serde(rename = "ETag")]
+    pub etag: String,
+}
+/// A part returned by ListParts
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct UploadedPart {
+    pub part_number: u32,
+    pub last_modified: DateTime<FixedOffset>,
+    #[serde(rename = "ETag")]
+    pub etag: String,
+    pub size: u64,
+    #[serde(
+        rename = "ChecksumCRC32",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub checksum_crc32: Option<String>,
+    #[serde(
+        rename = "ChecksumCRC32C",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub checksum_crc32c: Option<String>,
+    #[serde(
+        rename = "ChecksumSHA1",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub checksum_sha1: Option<String>,
+    #[serde(
+        rename = "ChecksumSHA256",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub checksum_sha256: Option<String>,
+    #[serde(
+        rename = "ChecksumCRC64NVME",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub checksum_crc64nvme: Option<String>,
+}
+impl From<UploadedPart> for Part {
+    fn from(value: UploadedPart) -> Self {
+        Self {
+            number: value.part_number,
+            etag: value.etag,
+            checksum_crc32: value.checksum_crc32,
+            checksum_crc32c: value.checksum_crc32c,
+            checksum_sha1: value.checksum_sha1,
+            checksum_sha256: value.checksum_sha256,
+            checksum_crc64nvme: value.checksum_crc64nvme,
+        }
+    }
+}
+/// [AWS Docs](https://docs.aws.amazon.com/AmazonS3/latest/API/API_ListParts.html)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ListPartsResult {
+    pub bucket: String,
+    pub key: String,
+    pub upload_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub part_number_marker: Option<u32>,
+    /// Pass as the part number marker of the next request if [ListPartsResult::is_truncated]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_part_number_marker: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_parts: Option<u32>,
+    #[serde(default)]
+    pub is_truncated: bool,
+    #[serde(rename = "Part", default)]
+    pub parts: Vec<UploadedPart>,
+}
 
 #[cfg(test)]
 mod tests {
-    use crate::multi_part::{CompleteMultipartUpload, Part};
+    use crate::{
+        checksum::ChecksumAlgorithm,
+        multi_part::{CompleteMultipartUpload, Part},
+    };
     #[test]
     pub fn complete_serialize_deserialize() {
         let part = Part {
             number: 1,
             etag: "etag1".into(),
+            ..Default::default()
         };
         let part_two = Part {
             number: 2,
             etag: "etag2".into(),
-        };
+            ..Default::default()
+        }
+        .with_checksum(ChecksumAlgorithm::Crc32, "AAAAAA==");
         let upload = CompleteMultipartUpload {
             parts: vec![part, part_two],
         };
         let serialized = quick_xml::se::to_string(&upload).unwrap();
         println!("Serialized: \n {}", serialized);
+        assert!(serialized.contains("<ChecksumCRC32>AAAAAA==</ChecksumCRC32>"));
+        assert_eq!(serialized.matches("Checksum").count(), 2);
         let deserialized: CompleteMultipartUpload = quick_xml::de::from_str(&serialized).unwrap();
         assert_eq!(upload, deserialized);
     }
+    #[test]
+    pub fn list_parts() {
+        let xml = r#"<ListPartsResult>
+            <Bucket>bucket</Bucket>
+            <Key>key.bin</Key>
+            <UploadId>upload-1</UploadId>
+            <PartNumberMarker>0</PartNumberMarker>
+            <NextPartNumberMarker>2</NextPartNumberMarker>
+            <MaxParts>2</MaxParts>
+            <IsTruncated>true</IsTruncated>
+            <Part><PartNumber>1</PartNumber><LastModified>2024-01-01T00:00:00.000Z</LastModified><ETag>"etag1"</ETag><Size>5242880</Size></Part>
+            <Part><PartNumber>2</PartNumber><LastModified>2024-01-01T00:00:00.000Z</LastModified><ETag>"etag2"</ETag><Size>100</Size><ChecksumCRC32>AAAAAA==</ChecksumCRC32></Part>
+        </ListPartsResult>"#;
+        let result: super::ListPartsResult = quick_xml::de::from_str(xml).unwrap();
+        assert!(result.is_truncated);
+        assert_eq!(result.next_part_number_marker, Some(2));
+        assert_eq!(result.parts.len(), 2);
+        assert_eq!(result.parts[1].etag, "\"etag2\"");
+        assert_eq!(
+            result.parts[1].checksum(ChecksumAlgorithm::Crc32),
+            Some("AAAAAA==")
+        );
+        assert_eq!(result.parts[0].checksum(ChecksumAlgorithm::Crc32), None);
+
+        let empty = r#"<ListPartsResult><Bucket>bucket</Bucket><Key>key.bin</Key><UploadId>upload-1</UploadId></ListPartsResult>"#;
+        let result: super::ListPartsResult = quick_xml::de::from_str(empty).unwrap();
+        assert!(result.parts.is_empty());
+    }
 }
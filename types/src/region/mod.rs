@@ -3,13 +3,14 @@ use std::{str::FromStr, sync::Arc};
 use serde::{Deserialize, Serialize};
 use url::Url;
 mod official;
-pub use official::OfficialRegion;
+pub use official::{InvalidPartitionError, InvalidRegionError, OfficialRegion, Partition};
 
 use crate::signature::SignatureVersions;
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum S3Implementation {
     AWS,
     Ceph,
+    #[default]
     GenericCustom,
 }
 pub trait RegionType {
@@ -29,6 +30,10 @@ pub trait RegionType {
     fn implementation(&self) -> S3Implementation {
         S3Implementation::AWS
     }
+    /// Returns true if buckets can be addressed as `{bucket}.{endpoint}`
+    fn supports_virtual_hosted_style(&self) -> bool {
+        self.implementation() == S3Implementation::AWS
+    }
 }
 impl<R: RegionType> RegionType for &R {
     fn name(&self) -> &str {
@@ -49,6 +54,9 @@ impl<R: RegionType> RegionType for &R {
     fn implementation(&self) -> S3Implementation {
         R::implementation(self)
     }
+    fn supports_virtual_hosted_style(&self) -> bool {
+        R::supports_virtual_hosted_style(self)
+    }
 }
 impl<R: RegionType> RegionType for Arc<R> {
     fn name(&self) -> &str {
@@ -69,11 +77,56 @@ impl<R: RegionType> RegionType for Arc<R> {
     fn implementation(&self) -> S3Implementation {
         R::implementation(self)
     }
+    fn supports_virtual_hosted_style(&self) -> bool {
+        R::supports_virtual_hosted_style(self)
+    }
 }
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct CustomRegion {
     pub endpoint: Url,
     pub name: Option<String>,
+    /// The endpoint accepts `{bucket}.{host}:{port}` addressing. e.g. MinIO with `MINIO_DOMAIN` or Ceph RGW with `rgw_dns_name`
+    #[serde(default)]
+    pub virtual_hosted: bool,
+    #[serde(default)]
+    pub implementation: S3Implementation,
+}
+impl CustomRegion {
+    pub fn new(endpoint: Url) -> Self {
+        Self {
+            endpoint,
+            name: None,
+            virtual_hosted: false,
+            implementation: S3Implementation::default(),
+        }
+    }
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+    pub fn with_virtual_hosted(mut self, virtual_hosted: bool) -> Self {
+        self.virtual_hosted = virtual_hosted;
+        self
+    }
+    pub fn with_implementation(mut self, implementation: S3Implementation) -> Self {
+        self.implementation = implementation;
+        self
+    }
+    /// The host of the endpoint including the port if it is not the default port of the scheme
+    pub fn host(&self) -> String {
+        let host = self.endpoint();
+        match self.endpoint.port() {
+            Some(port) => format!("{host}:{port}"),
+            None => host.to_string(),
+        }
+    }
+    /// The endpoint with the bucket prepended to the host. `{scheme}://{bucket}.{host}:{port}/`
+    pub fn bucket_url(&self, bucket: &str) -> Result<Url, url::ParseError> {
+        let mut url = self.endpoint.clone();
+        url.set_host(Some(&format!("{bucket}.{}", self.endpoint())))?;
+        url.set_path("/");
+        Ok(url)
+    }
 }
 impl RegionType for CustomRegion {
     fn name(&self) -> &str {
@@ -99,16 +152,19 @@ impl RegionType for CustomRegion {
     fn supported_signature_versions(&self) -> Vec<SignatureVersions> {
         vec![SignatureVersions::V4]
     }
+    fn implementation(&self) -> S3Implementation {
+        self.implementation.clone()
+    }
+    fn supports_virtual_hosted_style(&self) -> bool {
+        self.virtual_hosted
+    }
 }
 impl FromStr for CustomRegion {
     type Err = url::ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let endpoint = Url::parse(s)?;
-        Ok(Self {
-            endpoint,
-            name: None,
-        })
+        Ok(Self::new(endpoint))
     }
 }
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -147,8 +203,8 @@ impl RegionType for S3Region {
     }
     fn schema(&self) -> &str {
         match self {
-            S3Region::Official(region) => region.endpoint().split("://").next().unwrap_or("https"),
-            S3Region::Custom(region) => region.endpoint().split("://").next().unwrap_or("https"),
+            S3Region::Official(region) => region.schema(),
+            S3Region::Custom(region) => region.schema(),
         }
     }
 
@@ -165,6 +221,18 @@ impl RegionType for S3Region {
             S3Region::Custom(region) => region.supported_signature_versions(),
         }
     }
+    fn implementation(&self) -> S3Implementation {
+        match self {
+            S3Region::Official(region) => region.implementation(),
+            S3Region::Custom(region) => region.implementation(),
+        }
+    }
+    fn supports_virtual_hosted_style(&self) -> bool {
+        match self {
+            S3Region::Official(region) => region.supports_virtual_hosted_style(),
+            S3Region::Custom(region) => region.supports_virtual_hosted_style(),
+        }
+    }
 }
 
 struct S3RegionVisitor;
@@ -191,6 +259,8 @@ impl<'de> serde::de::Visitor<'de> for S3RegionVisitor {
     {
         let mut endpoint = None;
         let mut name = None;
+        let mut virtual_hosted = None;
+        let mut implementation = None;
         while let Some(key) = map.next_key::<String>()? {
             match key.as_str() {
                 "endpoint" => {
@@ -205,17 +275,32 @@ impl<'de> serde::de::Visitor<'de> for S3RegionVisitor {
                     }
                     name = Some(map.next_value()?);
                 }
-                _ => return Err(serde::de::Error::unknown_field(&key, &["endpoint", "name"])),
+                "virtual_hosted" => {
+                    if virtual_hosted.is_some() {
+                        return Err(serde::de::Error::duplicate_field("virtual_hosted"));
+                    }
+                    virtual_hosted = Some(map.next_value()?);
+                }
+                "implementation" => {
+                    if implementation.is_some() {
+                        return Err(serde::de::Error::duplicate_field("implementation"));
+                    }
+                    implementation = Some(map.next_value()?);
+                }
+                _ => {
+                    return Err(serde::de::Error::unknown_field(
+                        &key,
+                        &["endpoint", "name", "virtual_hosted", "implementation"],
+                    ));
+                }
             }
         }
         let region = match (endpoint, name) {
-            (Some(endpoint), Some(name)) => Ok(S3Region::Custom(CustomRegion {
-                endpoint,
-                name: Some(name),
-            })),
-            (Some(endpoint), None) => Ok(S3Region::Custom(CustomRegion {
+            (Some(endpoint), name) => Ok(S3Region::Custom(CustomRegion {
                 endpoint,
-                name: None,
+                name,
+                virtual_hosted: virtual_hosted.unwrap_or_default(),
+                implementation: implementation.unwrap_or_default(),
             })),
             (None, Some(name)) => {
                 let region = OfficialRegion::from_str(&name);
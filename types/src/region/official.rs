@@ -15,41 +15,125 @@ impl From<&str> for InvalidRegionError {
         Self(value.to_string())
     }
 }
-macro_rules! official_region {
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid partition: {0}")]
+pub struct InvalidPartitionError(pub String);
+/// Returns the S3 endpoint of a region. Regions can override the default `s3.{region}.{dns_suffix}`
+macro_rules! region_endpoint {
+    ($key:literal, $dns_suffix:literal) => {
+        concat!("s3.", $key, ".", $dns_suffix)
+    };
+    ($key:literal, $dns_suffix:literal, $endpoint:literal) => {
+        $endpoint
+    };
+}
+/// Generates [Partition] and [OfficialRegion] from a table of partitions and their regions.
+macro_rules! official_regions {
     (
         $(
-            $(#[$docs:meta])*
-            $name:ident{
-                endpoint: $endpoint:literal,
-                key: $key:literal
+            $(#[$partition_docs:meta])*
+            $partition:ident {
+                id: $id:literal,
+                dns_suffix: $dns_suffix:literal,
+                regions: {
+                    $(
+                        $(#[$docs:meta])*
+                        $name:ident {
+                            key: $key:literal
+                            $(, endpoint: $endpoint:literal)?
+                        }
+                    ),* $(,)?
+                }
             }
-        ),*
+        ),* $(,)?
     ) => {
+        /// A group of AWS regions that share a DNS suffix and ARN prefix.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Partition {
+            $(
+                $(#[$partition_docs])*
+                $partition,
+            )*
+        }
+        impl Partition {
+            /// The partition id. e.g. `aws-cn`
+            pub fn id(&self) -> &'static str {
+                match self {
+                    $(Partition::$partition => $id,)*
+                }
+            }
+            /// The domain endpoints of the partition end with. e.g. `amazonaws.com.cn`
+            pub fn dns_suffix(&self) -> &'static str {
+                match self {
+                    $(Partition::$partition => $dns_suffix,)*
+                }
+            }
+            /// The prefix of ARNs in the partition. e.g. `arn:aws-cn`
+            pub fn arn_prefix(&self) -> &'static str {
+                match self {
+                    $(Partition::$partition => concat!("arn:", $id),)*
+                }
+            }
+            /// All regions of the partition
+            pub fn regions(&self) -> &'static [OfficialRegion] {
+                match self {
+                    $(Partition::$partition => &[$(OfficialRegion::$name,)*],)*
+                }
+            }
+        }
+        impl FromStr for Partition {
+            type Err = InvalidPartitionError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $($id => Ok(Partition::$partition),)*
+                    _ => Err(InvalidPartitionError(s.to_string())),
+                }
+            }
+        }
+        impl Display for Partition {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.id())
+            }
+        }
         #[derive(Debug, Clone, PartialEq, Eq)]
         pub enum OfficialRegion {
             $(
-                $(#[$docs])*
-                $name,
+                $(
+                    $(#[$docs])*
+                    $name,
+                )*
             )*
         }
+        impl OfficialRegion {
+            pub fn partition(&self) -> Partition {
+                match self {
+                    $($(OfficialRegion::$name => Partition::$partition,)*)*
+                }
+            }
+            /// The regional STS endpoint
+            pub fn sts_endpoint(&self) -> &'static str {
+                match self {
+                    $($(OfficialRegion::$name => concat!("https://sts.", $key, ".", $dns_suffix),)*)*
+                }
+            }
+        }
         impl RegionType for OfficialRegion {
             fn name(&self) -> &str {
                 match self {
-                    $(OfficialRegion::$name => $key,)*
+                    $($(OfficialRegion::$name => $key,)*)*
                 }
             }
             fn schema(&self) -> &str {
-                return "https";
+                "https"
             }
             fn endpoint(&self) -> &str {
                 match self {
-                    $(OfficialRegion::$name => $endpoint,)*
+                    $($(OfficialRegion::$name => region_endpoint!($key, $dns_suffix $(, $endpoint)?),)*)*
                 }
             }
             fn endpoint_url(&self) -> Url {
-                match self {
-                    $(OfficialRegion::$name => Url::parse(concat!("https://",$endpoint)).unwrap(),)*
-                }
+                Url::parse(&format!("https://{}", self.endpoint())).expect("Official endpoints are valid urls")
             }
             fn implementation(&self) -> S3Implementation {
                 S3Implementation::AWS
@@ -59,145 +143,138 @@ macro_rules! official_region {
             type Err = InvalidRegionError;
 
             fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let endpoint = s.strip_prefix("https://").unwrap_or(s);
                 match s {
-                    $(
-                        $key | $endpoint | concat!("https://", $endpoint) => Ok(OfficialRegion::$name),
-                    )*
+                    $($($key => return Ok(OfficialRegion::$name),)*)*
+                    _ => {}
+                }
+                match endpoint {
+                    $($(region_endpoint!($key, $dns_suffix $(, $endpoint)?) => Ok(OfficialRegion::$name),)*)*
                     _ => Err(InvalidRegionError::from(s)),
                 }
             }
         }
         impl Display for OfficialRegion {
             fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                match self {
-                    $(OfficialRegion::$name => write!(f, "{}", $key),)*
-                }
+                f.write_str(self.name())
             }
         }
     };
 }
 
-official_region!(
-    /// The US East (N. Virginia) region.
-    UsEast1 {
-        endpoint: "s3.amazonaws.com",
-        key: "us-east-1"
-    },
-        /// us-east-2
-    UsEast2{
-        endpoint: "s3.us-east-2.amazonaws.com",
-        key: "us-east-2"
-    },
-    /// us-west-1
-    UsWest1{
-        endpoint: "s3.us-west-1.amazonaws.com",
-        key: "us-west-1"
-    },
-    /// us-west-2
-    UsWest2{
-        endpoint: "s3.us-west-2.amazonaws.com",
-        key: "us-west-2"
-    },
-    /// ca-central-1
-    CaCentral1{
-        endpoint: "s3.ca-central-1.amazonaws.com",
-        key: "ca-central-1"
-    },
-    /// af-south-1
-    AfSouth1{
-        endpoint: "s3.af-south-1.amazonaws.com",
-        key: "af-south-1"
-    },
-    /// ap-east-1
-    ApEast1{
-        endpoint: "s3.ap-east-1.amazonaws.com",
-        key: "ap-east-1"
-    },
-    /// ap-south-1
-    ApSouth1{
-        endpoint: "s3.ap-south-1.amazonaws.com",
-        key: "ap-south-1"
-    },
-    /// ap-northeast-1
-    ApNortheast1{
-        endpoint: "s3.ap-northeast-1.amazonaws.com",
-        key: "ap-northeast-1"
-    },
-    /// ap-northeast-2
-    ApNortheast2{
-        endpoint: "s3.ap-northeast-2.amazonaws.com",
-        key: "ap-northeast-2"
-    },
-    /// ap-northeast-3
-    ApNortheast3{
-        endpoint: "s3.ap-northeast-3.amazonaws.com",
-        key: "ap-northeast-3"
-    },
-    /// ap-southeast-1
-    ApSoutheast1{
-        endpoint: "s3.ap-southeast-1.amazonaws.com",
-        key: "ap-southeast-1"
-    },
-    /// ap-southeast-2
-    ApSoutheast2{
-        endpoint: "s3.ap-southeast-2.amazonaws.com",
-        key: "ap-southeast-2"
-    },
-    /// cn-north-1
-    CnNorth1{
-        endpoint: "s3.cn-north-1.amazonaws.com",
-        key: "cn-north-1"
-    },
-    /// cn-northwest-1
-    CnNorthwest1{
-        endpoint: "s3.cn-northwest-1.amazonaws.com",
-        key: "cn-northwest-1"
-    },
-    /// eu-north-1
-    EuNorth1{
-        endpoint: "s3.eu-north-1.amazonaws.com",
-        key: "eu-north-1"
-    },
-    /// eu-central-1
-    EuCentral1{
-        endpoint: "s3.eu-central-1.amazonaws.com",
-        key: "eu-central-1"
-    },
-    /// eu-central-2
-    EuCentral2{
-        endpoint: "s3.eu-central-2.amazonaws.com",
-        key: "eu-central-2"
-    },
-    /// eu-west-1
-    EuWest1{
-        endpoint: "s3.eu-west-1.amazonaws.com",
-        key: "eu-west-1"
+official_regions!(
+    /// Commercial AWS regions
+    Aws {
+        id: "aws",
+        dns_suffix: "amazonaws.com",
+        regions: {
+            /// The US East (N. Virginia) region.
+            UsEast1 {
+                key: "us-east-1",
+                endpoint: "s3.amazonaws.com"
+            },
+            /// us-east-2
+            UsEast2 { key: "us-east-2" },
+            /// us-west-1
+            UsWest1 { key: "us-west-1" },
+            /// us-west-2
+            UsWest2 { key: "us-west-2" },
+            /// ca-central-1
+            CaCentral1 { key: "ca-central-1" },
+            /// ca-west-1
+            CaWest1 { key: "ca-west-1" },
+            /// mx-central-1
+            MxCentral1 { key: "mx-central-1" },
+            /// af-south-1
+            AfSouth1 { key: "af-south-1" },
+            /// ap-east-1
+            ApEast1 { key: "ap-east-1" },
+            /// ap-south-1
+            ApSouth1 { key: "ap-south-1" },
+            /// ap-south-2
+            ApSouth2 { key: "ap-south-2" },
+            /// ap-northeast-1
+            ApNortheast1 { key: "ap-northeast-1" },
+            /// ap-northeast-2
+            ApNortheast2 { key: "ap-northeast-2" },
+            /// ap-northeast-3
+            ApNortheast3 { key: "ap-northeast-3" },
+            /// ap-southeast-1
+            ApSoutheast1 { key: "ap-southeast-1" },
+            /// ap-southeast-2
+            ApSoutheast2 { key: "ap-southeast-2" },
+            /// ap-southeast-3
+            ApSoutheast3 { key: "ap-southeast-3" },
+            /// ap-southeast-4
+            ApSoutheast4 { key: "ap-southeast-4" },
+            /// eu-north-1
+            EuNorth1 { key: "eu-north-1" },
+            /// eu-central-1
+            EuCentral1 { key: "eu-central-1" },
+            /// eu-central-2
+            EuCentral2 { key: "eu-central-2" },
+            /// eu-south-1
+            EuSouth1 { key: "eu-south-1" },
+            /// eu-south-2
+            EuSouth2 { key: "eu-south-2" },
+            /// eu-west-1
+            EuWest1 { key: "eu-west-1" },
+            /// eu-west-2
+            EuWest2 { key: "eu-west-2" },
+            /// eu-west-3
+            EuWest3 { key: "eu-west-3" },
+            /// il-central-1
+            IlCentral1 { key: "il-central-1" },
+            /// me-central-1
+            MeCentral1 { key: "me-central-1" },
+            /// me-south-1
+            MeSouth1 { key: "me-south-1" },
+            /// sa-east-1
+            SaEast1 { key: "sa-east-1" },
+        }
     },
-    /// eu-west-2
-    EuWest2{
-        endpoint: "s3.eu-west-2.amazonaws.com",
-        key: "eu-west-2"
+    /// AWS China regions
+    AwsCn {
+        id: "aws-cn",
+        dns_suffix: "amazonaws.com.cn",
+        regions: {
+            /// cn-north-1
+            CnNorth1 { key: "cn-north-1" },
+            /// cn-northwest-1
+            CnNorthwest1 { key: "cn-northwest-1" },
+        }
     },
-    /// eu-west-3
-    EuWest3{
-        endpoint: "s3.eu-west-3.amazonaws.com",
-        key: "eu-west-3"
+    /// AWS GovCloud (US) regions
+    AwsUsGov {
+        id: "aws-us-gov",
+        dns_suffix: "amazonaws.com",
+        regions: {
+            /// us-gov-west-1
+            UsGovWest1 { key: "us-gov-west-1" },
+            /// us-gov-east-1
+            UsGovEast1 { key: "us-gov-east-1" },
+        }
     },
-    /// il-central-1
-    IlCentral1{
-        endpoint: "s3.il-central-1.amazonaws.com",
-        key: "il-central-1"
+    /// AWS ISO (US) regions
+    AwsIso {
+        id: "aws-iso",
+        dns_suffix: "c2s.ic.gov",
+        regions: {
+            /// us-iso-east-1
+            UsIsoEast1 { key: "us-iso-east-1" },
+            /// us-iso-west-1
+            UsIsoWest1 { key: "us-iso-west-1" },
+        }
     },
-    /// me-south-1
-    MeSouth1{
-        endpoint: "s3.me-south-1.amazonaws.com",
-        key: "me-south-1"
+    /// AWS ISOB (US) regions
+    AwsIsoB {
+        id: "aws-iso-b",
+        dns_suffix: "sc2s.sgov.gov",
+        regions: {
+            /// us-isob-east-1
+            UsIsobEast1 { key: "us-isob-east-1" },
+        }
     },
-    /// sa-east-1
-    SaEast1{
-        endpoint: "s3.sa-east-1.amazonaws.com",
-        key: "sa-east-1"
-    }
 );
 #[allow(clippy::derivable_impls)]
 impl Default for OfficialRegion {
@@ -248,3 +325,58 @@ impl serde::Serialize for OfficialRegion {
         serializer.serialize_str(self.name())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partitions() {
+        assert_eq!(OfficialRegion::CnNorth1.partition(), Partition::AwsCn);
+        assert_eq!(
+            OfficialRegion::CnNorthwest1.endpoint(),
+            "s3.cn-northwest-1.amazonaws.com.cn"
+        );
+        assert_eq!(
+            OfficialRegion::CnNorth1.sts_endpoint(),
+            "https://sts.cn-north-1.amazonaws.com.cn"
+        );
+        assert_eq!(Partition::AwsUsGov.arn_prefix(), "arn:aws-us-gov");
+        assert_eq!(
+            OfficialRegion::UsGovWest1.endpoint_url().as_str(),
+            "https://s3.us-gov-west-1.amazonaws.com/"
+        );
+        assert_eq!(OfficialRegion::UsEast1.endpoint(), "s3.amazonaws.com");
+        assert!(
+            Partition::Aws
+                .regions()
+                .contains(&OfficialRegion::MxCentral1)
+        );
+        assert_eq!("aws-iso-b".parse(), Ok(Partition::AwsIsoB));
+    }
+    #[test]
+    fn from_str() {
+        for partition in [
+            Partition::Aws,
+            Partition::AwsCn,
+            Partition::AwsUsGov,
+            Partition::AwsIso,
+            Partition::AwsIsoB,
+        ] {
+            for region in partition.regions() {
+                assert_eq!(region.name().parse().as_ref(), Ok(region));
+                assert_eq!(region.endpoint().parse().as_ref(), Ok(region));
+                assert_eq!(region.endpoint_url().host_str(), Some(region.endpoint()));
+            }
+        }
+        assert_eq!(
+            "https://s3.ap-southeast-4.amazonaws.com".parse(),
+            Ok(OfficialRegion::ApSoutheast4)
+        );
+        assert!(
+            "s3.cn-north-1.amazonaws.com"
+                .parse::<OfficialRegion>()
+                .is_err()
+        );
+    }
+}